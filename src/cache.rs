@@ -0,0 +1,265 @@
+//! Keeps provider replies on disk so the same request inside its time to live
+//! doesn't go over the network again.
+
+use std::{
+    cell::Cell,
+    collections::HashMap,
+    fmt::Debug,
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Error;
+use serde::{Deserialize, Serialize};
+
+use crate::provider::MarketDataProvider;
+
+/// Used for any endpoint that doesn't have its own time to live
+pub const DEFAULT_TTL_SECS: u64 = 60;
+
+/// How long a reply stays good for each endpoint unless config.json says otherwise
+pub fn default_ttls() -> HashMap<String, u64> {
+    [
+        ("/stock/symbol", 24 * 60 * 60),
+        ("/stock/profile2", 24 * 60 * 60),
+        ("/company-news", 15 * 60),
+        ("/news", 5 * 60),
+        ("/quote", 5),
+        ("/stock/candle", 15 * 60),
+        ("/stock/metric", 6 * 60 * 60),
+        ("/stock/insider-sentiment", 6 * 60 * 60),
+        ("/stock/insider-transactions", 60 * 60),
+        ("/stock/financials-reported", 24 * 60 * 60),
+        ("/stock/filings", 60 * 60),
+        ("/stock/recommendation", 6 * 60 * 60),
+        ("/calendar/earnings", 60 * 60),
+        ("/stock/earnings", 6 * 60 * 60),
+        ("/calendar/ipo", 60 * 60),
+        ("/stock/peers", 24 * 60 * 60),
+        ("/forex/exchange", 24 * 60 * 60),
+        ("/forex/symbol", 24 * 60 * 60),
+        ("/forex/rates", 5 * 60),
+        ("/crypto/exchange", 24 * 60 * 60),
+        ("/crypto/symbol", 24 * 60 * 60),
+        ("/crypto/candle", 15 * 60),
+    ]
+    .into_iter()
+    .map(|(endpoint, secs)| (endpoint.to_string(), secs))
+    .collect()
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheEntry {
+    // Seconds since the epoch
+    fetched_at: u64,
+    text: String,
+}
+
+/// Wraps another provider and answers from the cache directory when it can
+#[derive(Debug)]
+pub struct CachedProvider {
+    inner: Box<dyn MarketDataProvider>,
+    dir: PathBuf,
+    ttls: HashMap<String, u64>,
+    // Age of the oldest cached reply handed out since take_cache_age was last called
+    oldest_hit: Cell<Option<Duration>>,
+}
+
+impl CachedProvider {
+    /// ttls from the config are laid over the defaults
+    pub fn new(
+        inner: Box<dyn MarketDataProvider>,
+        dir: PathBuf,
+        ttls: &HashMap<String, u64>,
+    ) -> Self {
+        let mut all_ttls = default_ttls();
+        all_ttls.extend(
+            ttls.iter()
+                .map(|(endpoint, secs)| (endpoint.clone(), *secs)),
+        );
+        Self {
+            inner,
+            dir,
+            ttls: all_ttls,
+            oldest_hit: Cell::new(None),
+        }
+    }
+
+    fn ttl(&self, endpoint: &str) -> Duration {
+        let secs = self.ttls.get(endpoint).copied().unwrap_or(DEFAULT_TTL_SECS);
+        Duration::from_secs(secs)
+    }
+
+    /// /stock/symbol?exchange=US becomes stock_symbol__exchange=US.json
+    fn path(&self, endpoint: &str, query: &[(&str, String)]) -> PathBuf {
+        let mut query = query.to_vec();
+        query.sort();
+        let key = std::iter::once(endpoint.trim_matches('/').to_string())
+            .chain(query.iter().map(|(key, value)| format!("{key}={value}")))
+            .collect::<Vec<_>>()
+            .join("__")
+            .replace(
+                |c: char| !(c.is_alphanumeric() || c == '=' || c == '-'),
+                "_",
+            );
+        self.dir.join(format!("{key}.json"))
+    }
+
+    fn read(&self, endpoint: &str, query: &[(&str, String)]) -> Option<(String, Duration)> {
+        let text = std::fs::read_to_string(self.path(endpoint, query)).ok()?;
+        let entry: CacheEntry = serde_json::from_str(&text).ok()?;
+        let age = Duration::from_secs(now().saturating_sub(entry.fetched_at));
+        (age < self.ttl(endpoint)).then_some((entry.text, age))
+    }
+
+    // A cache that can't be written to just means the next request goes over the network
+    fn write(&self, endpoint: &str, query: &[(&str, String)], text: &str) {
+        let entry = CacheEntry {
+            fetched_at: now(),
+            text: text.to_string(),
+        };
+        if let Ok(json) = serde_json::to_string(&entry) {
+            let _ = std::fs::create_dir_all(&self.dir)
+                .and_then(|_| std::fs::write(self.path(endpoint, query), json));
+        }
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+impl MarketDataProvider for CachedProvider {
+    fn get_text(&self, endpoint: &str, query: &[(&str, String)]) -> Result<String, Error> {
+        if self.ttl(endpoint).is_zero() {
+            return self.inner.get_text(endpoint, query);
+        }
+        if let Some((text, age)) = self.read(endpoint, query) {
+            let oldest = self.oldest_hit.get().map_or(age, |oldest| oldest.max(age));
+            self.oldest_hit.set(Some(oldest));
+            return Ok(text);
+        }
+        let text = self.inner.get_text(endpoint, query)?;
+        // Finnhub sometimes answers 200 with {"error": "..."}, which shouldn't stick around
+        if !text.starts_with("{\"error\"") {
+            self.write(endpoint, query, &text);
+        }
+        Ok(text)
+    }
+
+    fn take_cache_age(&self) -> Option<Duration> {
+        self.oldest_hit.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use super::*;
+
+    /// Counts how many requests got past the cache
+    #[derive(Debug, Default)]
+    struct Counter {
+        calls: Arc<AtomicUsize>,
+    }
+
+    impl MarketDataProvider for Counter {
+        fn get_text(&self, _endpoint: &str, _query: &[(&str, String)]) -> Result<String, Error> {
+            let calls = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok(format!("{{\"calls\":{calls}}}"))
+        }
+    }
+
+    /// Removes the cache directory when the test is done with it
+    struct TempDir(PathBuf);
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn cached(name: &str, ttls: &[(&str, u64)]) -> (CachedProvider, Arc<AtomicUsize>, TempDir) {
+        let dir = std::env::temp_dir().join(format!(
+            "finance-tool-cache-test-{}-{name}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        let counter = Counter::default();
+        let calls = counter.calls.clone();
+        let ttls = ttls
+            .iter()
+            .map(|(endpoint, secs)| (endpoint.to_string(), *secs))
+            .collect();
+        (
+            CachedProvider::new(Box::new(counter), dir.clone(), &ttls),
+            calls,
+            TempDir(dir),
+        )
+    }
+
+    fn query() -> Vec<(&'static str, String)> {
+        vec![("symbol", "AAPL".to_string())]
+    }
+
+    #[test]
+    fn answers_from_the_cache_inside_the_ttl() {
+        let (provider, calls, _dir) = cached("fresh", &[]);
+        let first = provider.get_text("/stock/profile2", &query()).unwrap();
+        let second = provider.get_text("/stock/profile2", &query()).unwrap();
+        assert_eq!(first, second);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert!(provider.take_cache_age().is_some());
+        assert!(provider.take_cache_age().is_none());
+    }
+
+    #[test]
+    fn goes_to_the_source_once_the_ttl_is_up() {
+        let (provider, calls, _dir) = cached("expired", &[]);
+        // A reply fetched a day and a second ago, older than /stock/profile2's day
+        let entry = CacheEntry {
+            fetched_at: now() - 24 * 60 * 60 - 1,
+            text: "old".to_string(),
+        };
+        std::fs::create_dir_all(&provider.dir).unwrap();
+        std::fs::write(
+            provider.path("/stock/profile2", &query()),
+            serde_json::to_string(&entry).unwrap(),
+        )
+        .unwrap();
+        let text = provider.get_text("/stock/profile2", &query()).unwrap();
+        assert_eq!(text, "{\"calls\":1}");
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert!(provider.take_cache_age().is_none());
+    }
+
+    #[test]
+    fn zero_ttl_from_the_config_turns_caching_off() {
+        let (provider, calls, _dir) = cached("off", &[("/quote", 0)]);
+        provider.get_text("/quote", &query()).unwrap();
+        provider.get_text("/quote", &query()).unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn path_ignores_query_order() {
+        let (provider, _, _dir) = cached("path", &[]);
+        let a = provider.path(
+            "/stock/candle",
+            &[("symbol", "AAPL".into()), ("from", "1".into())],
+        );
+        let b = provider.path(
+            "/stock/candle",
+            &[("from", "1".into()), ("symbol", "AAPL".into())],
+        );
+        assert_eq!(a, b);
+        assert!(a.ends_with("stock_candle__from=1__symbol=AAPL.json"));
+    }
+}
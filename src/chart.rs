@@ -0,0 +1,274 @@
+//! Price charts for the Results window, drawn with tui's `Chart` widget.
+
+use chrono::{TimeZone, Utc};
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Color, Style},
+    symbols::Marker,
+    text::Span,
+    widgets::{Axis, Block, Chart, Dataset, GraphType},
+    Frame,
+};
+
+use crate::api::{Candles, Resolution};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChartMode {
+    Line,
+    Ohlc,
+}
+
+impl ChartMode {
+    pub fn toggle(&mut self) {
+        *self = match self {
+            ChartMode::Line => ChartMode::Ohlc,
+            ChartMode::Ohlc => ChartMode::Line,
+        }
+    }
+}
+
+impl std::fmt::Display for ChartMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let output = match self {
+            ChartMode::Line => "line",
+            ChartMode::Ohlc => "OHLC",
+        };
+        write!(f, "{}", output)
+    }
+}
+
+/// One open/high/low/close bar, possibly made out of a few candles squashed together
+/// when there are more candles than the terminal has room for
+#[derive(Debug, Clone, Copy)]
+struct Bar {
+    time: i64,
+    open: f64,
+    high: f64,
+    low: f64,
+    close: f64,
+}
+
+/// Everything needed to draw a set of candles, kept in State so it can be
+/// redrawn at whatever size the terminal currently is
+#[derive(Debug, Clone)]
+pub struct CandleChart {
+    pub symbol: String,
+    pub resolution: Resolution,
+    pub candles: Candles,
+    pub mode: ChartMode,
+}
+
+impl CandleChart {
+    pub fn new(symbol: String, resolution: Resolution, candles: Candles) -> Self {
+        Self {
+            symbol,
+            resolution,
+            candles,
+            mode: ChartMode::Line,
+        }
+    }
+
+    fn bars(&self) -> Vec<Bar> {
+        let Candles { c, h, l, o, t, .. } = &self.candles;
+        (0..t.len().min(c.len()).min(h.len()).min(l.len()).min(o.len()))
+            .map(|i| Bar {
+                time: t[i],
+                open: o[i],
+                high: h[i],
+                low: l[i],
+                close: c[i],
+            })
+            .collect()
+    }
+
+    /// Merges neighbouring bars until there are no more than `max_bars` of them
+    fn squash(bars: Vec<Bar>, max_bars: usize) -> Vec<Bar> {
+        if bars.len() <= max_bars || max_bars == 0 {
+            return bars;
+        }
+        let per_bar = bars.len().div_ceil(max_bars);
+        bars.chunks(per_bar)
+            .map(|chunk| Bar {
+                time: chunk[0].time,
+                open: chunk[0].open,
+                high: chunk.iter().map(|bar| bar.high).fold(f64::MIN, f64::max),
+                low: chunk.iter().map(|bar| bar.low).fold(f64::MAX, f64::min),
+                close: chunk[chunk.len() - 1].close,
+            })
+            .collect()
+    }
+
+    fn time_label(&self, timestamp: i64) -> String {
+        let format = match self.resolution {
+            Resolution::Day | Resolution::Week | Resolution::Month => "%Y-%m-%d",
+            _ => "%m-%d %H:%M",
+        };
+        Utc.timestamp(timestamp, 0).format(format).to_string()
+    }
+
+    pub fn render<B: Backend>(&self, f: &mut Frame<B>, area: Rect, block: Block) {
+        let all_bars = self.bars();
+        // Room for the y axis labels on the left and the borders
+        let plot_width = area.width.saturating_sub(12) as usize;
+        let bars = match self.mode {
+            ChartMode::Line => all_bars,
+            // Each OHLC bar needs a column for the open tick, the wick and the close tick
+            ChartMode::Ohlc => Self::squash(all_bars, plot_width / 2),
+        };
+
+        let low = bars.iter().map(|bar| bar.low).fold(f64::MAX, f64::min);
+        let high = bars.iter().map(|bar| bar.high).fold(f64::MIN, f64::max);
+        let padding = ((high - low) * 0.05).max(0.01);
+        let y_bounds = [low - padding, high + padding];
+        let x_bounds = [-1.0, bars.len() as f64];
+
+        let line_points = bars
+            .iter()
+            .enumerate()
+            .map(|(index, bar)| (index as f64, bar.close))
+            .collect::<Vec<_>>();
+
+        // Drawn as one connected line: open tick, wick down to low, up to high, then the close tick
+        let ohlc_points = bars
+            .iter()
+            .enumerate()
+            .map(|(index, bar)| {
+                let x = index as f64;
+                vec![
+                    (x - 0.4, bar.open),
+                    (x, bar.open),
+                    (x, bar.low),
+                    (x, bar.high),
+                    (x, bar.close),
+                    (x + 0.4, bar.close),
+                ]
+            })
+            .collect::<Vec<_>>();
+
+        let datasets = match self.mode {
+            ChartMode::Line => vec![Dataset::default()
+                .name(self.symbol.clone())
+                .marker(Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(Color::LightCyan))
+                .data(&line_points)],
+            ChartMode::Ohlc => bars
+                .iter()
+                .zip(ohlc_points.iter())
+                .map(|(bar, points)| {
+                    let colour = if bar.close >= bar.open {
+                        Color::Green
+                    } else {
+                        Color::Red
+                    };
+                    Dataset::default()
+                        .marker(Marker::Braille)
+                        .graph_type(GraphType::Line)
+                        .style(Style::default().fg(colour))
+                        .data(points)
+                })
+                .collect(),
+        };
+
+        // More labels when there's more room for them
+        let x_label_count = (area.width as usize / 20).clamp(2, 6);
+        let y_label_count = (area.height as usize / 4).clamp(2, 6);
+
+        let x_labels = (0..x_label_count)
+            .filter_map(|n| {
+                let index = n * bars.len().saturating_sub(1) / (x_label_count - 1);
+                bars.get(index)
+                    .map(|bar| Span::raw(self.time_label(bar.time)))
+            })
+            .collect::<Vec<_>>();
+        let y_labels = (0..y_label_count)
+            .map(|n| {
+                let value = y_bounds[0]
+                    + (y_bounds[1] - y_bounds[0]) * n as f64 / (y_label_count - 1) as f64;
+                Span::raw(format!("{value:.2}"))
+            })
+            .collect::<Vec<_>>();
+
+        let chart = Chart::new(datasets)
+            .block(block)
+            .style(Style::default().fg(Color::White).bg(Color::Black))
+            .x_axis(
+                Axis::default()
+                    .style(Style::default().fg(Color::Gray))
+                    .bounds(x_bounds)
+                    .labels(x_labels),
+            )
+            .y_axis(
+                Axis::default()
+                    .style(Style::default().fg(Color::Gray))
+                    .bounds(y_bounds)
+                    .labels(y_labels),
+            );
+        f.render_widget(chart, area);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chart(closes: &[f64]) -> CandleChart {
+        let candles = Candles {
+            c: closes.to_vec(),
+            h: closes.iter().map(|close| close + 1.0).collect(),
+            l: closes.iter().map(|close| close - 1.0).collect(),
+            o: closes.iter().map(|close| close - 0.5).collect(),
+            s: "ok".to_string(),
+            t: (0..closes.len() as i64).map(|day| day * 86_400).collect(),
+            v: vec![0.0; closes.len()],
+        };
+        CandleChart::new("AAPL".to_string(), Resolution::Day, candles)
+    }
+
+    #[test]
+    fn toggle_switches_between_line_and_ohlc() {
+        let mut mode = ChartMode::Line;
+        mode.toggle();
+        assert_eq!(mode, ChartMode::Ohlc);
+        mode.toggle();
+        assert_eq!(mode, ChartMode::Line);
+    }
+
+    #[test]
+    fn bars_stop_at_the_shortest_series() {
+        let mut chart = chart(&[10.0, 11.0, 12.0]);
+        chart.candles.o.pop();
+        assert_eq!(chart.bars().len(), 2);
+    }
+
+    #[test]
+    fn squash_keeps_the_range_of_each_group() {
+        let chart = chart(&[10.0, 14.0, 12.0, 11.0, 13.0]);
+        let bars = CandleChart::squash(chart.bars(), 2);
+        assert_eq!(bars.len(), 2);
+        // The first three candles, then the last two
+        assert_eq!(bars[0].time, 0);
+        assert_eq!(bars[0].open, 9.5);
+        assert_eq!(bars[0].high, 15.0);
+        assert_eq!(bars[0].low, 9.0);
+        assert_eq!(bars[0].close, 12.0);
+        assert_eq!(bars[1].time, 3 * 86_400);
+        assert_eq!(bars[1].close, 13.0);
+    }
+
+    #[test]
+    fn squash_leaves_few_enough_bars_alone() {
+        let chart = chart(&[10.0, 11.0]);
+        assert_eq!(CandleChart::squash(chart.bars(), 2).len(), 2);
+        assert_eq!(CandleChart::squash(chart.bars(), 0).len(), 2);
+    }
+
+    #[test]
+    fn time_label_shows_the_time_for_intraday_candles() {
+        let mut chart = chart(&[]);
+        assert_eq!(chart.time_label(86_400), "1970-01-02");
+        chart.resolution = Resolution::Sixty;
+        assert_eq!(chart.time_label(86_400 + 3_600), "01-02 01:00");
+    }
+}
@@ -0,0 +1,365 @@
+//! Peer comparison for the Results window: a company and its peers (or any symbols typed
+//! in) side by side on valuation, margins, beta and the last year's return, sortable by
+//! any column.
+
+use std::cmp::Ordering;
+
+use anyhow::Error;
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Row, Table, TableState},
+    Frame,
+};
+
+use crate::api::{BasicFinancials, CompanyProfile};
+
+/// Which symbols to compare, typed into the search box as SYMBOL for a company and its
+/// peers or as two or more symbols to compare just those
+#[derive(Debug, Clone)]
+pub enum PeerSet {
+    PeersOf(String),
+    Symbols(Vec<String>),
+}
+
+impl PeerSet {
+    pub fn parse(input: &str, current: Option<&str>) -> Result<Self, Error> {
+        let mut symbols = Vec::new();
+        for symbol in input.split_whitespace().map(str::to_uppercase) {
+            if !symbols.contains(&symbol) {
+                symbols.push(symbol);
+            }
+        }
+        match (symbols.len(), current) {
+            (0, Some(current)) => Ok(PeerSet::PeersOf(current.to_string())),
+            (0, None) => Err(anyhow::anyhow!(
+                "Type a symbol for it and its peers, or a few symbols to compare, e.g. AAPL MSFT GOOGL"
+            )),
+            (1, _) => Ok(PeerSet::PeersOf(symbols.remove(0))),
+            _ => Ok(PeerSet::Symbols(symbols)),
+        }
+    }
+
+    /// The symbol the comparison is for, highlighted in the table
+    pub fn main_symbol(&self) -> &str {
+        match self {
+            PeerSet::PeersOf(symbol) => symbol,
+            PeerSet::Symbols(symbols) => &symbols[0],
+        }
+    }
+}
+
+/// One row of the table. Either request can fail on its own (e.g. a fund with no
+/// financials), so whatever came back is kept along with why the rest didn't.
+#[derive(Debug, Clone, Default)]
+pub struct PeerMetrics {
+    pub symbol: String,
+    pub name: Option<String>,
+    // In millions, like everything else from Finnhub
+    pub market_cap: Option<f64>,
+    pub pe: Option<f64>,
+    pub gross_margin: Option<f64>,
+    pub operating_margin: Option<f64>,
+    pub net_margin: Option<f64>,
+    pub beta: Option<f64>,
+    pub week_52_return: Option<f64>,
+    pub error: Option<String>,
+}
+
+impl PeerMetrics {
+    pub fn new(
+        symbol: String,
+        profile: Result<CompanyProfile, Error>,
+        financials: Result<BasicFinancials, Error>,
+    ) -> Self {
+        let mut row = PeerMetrics {
+            symbol,
+            ..Default::default()
+        };
+        let mut errors = Vec::new();
+        match financials {
+            Ok(financials) => {
+                let metric = financials.metric;
+                row.market_cap = metric.market_capitalization;
+                row.pe = metric.pe_ttm;
+                row.gross_margin = metric.gross_margin_ttm;
+                row.operating_margin = metric.operating_margin_ttm;
+                row.net_margin = metric.net_margin_ttm;
+                row.beta = metric.beta;
+                row.week_52_return = metric.week_52_return;
+            }
+            Err(e) => errors.push(format!("financials: {e}")),
+        }
+        // The profile's market cap is the more up to date of the two
+        match profile {
+            Ok(profile) => {
+                row.name = Some(profile.name);
+                if profile.market_capitalization > 0.0 {
+                    row.market_cap = Some(profile.market_capitalization);
+                }
+            }
+            Err(e) => errors.push(format!("profile: {e}")),
+        }
+        if !errors.is_empty() {
+            row.error = Some(errors.join(", "));
+        }
+        row
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.name.is_none() && self.market_cap.is_none() && self.pe.is_none()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareColumn {
+    Symbol,
+    Name,
+    MarketCap,
+    Pe,
+    GrossMargin,
+    OperatingMargin,
+    NetMargin,
+    Beta,
+    Return,
+}
+
+impl CompareColumn {
+    const ALL: [CompareColumn; 9] = [
+        CompareColumn::Symbol,
+        CompareColumn::Name,
+        CompareColumn::MarketCap,
+        CompareColumn::Pe,
+        CompareColumn::GrossMargin,
+        CompareColumn::OperatingMargin,
+        CompareColumn::NetMargin,
+        CompareColumn::Beta,
+        CompareColumn::Return,
+    ];
+
+    pub fn next(self) -> Self {
+        let index = Self::ALL
+            .iter()
+            .position(|column| *column == self)
+            .unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    fn value(self, row: &PeerMetrics) -> Option<f64> {
+        match self {
+            CompareColumn::Symbol | CompareColumn::Name => None,
+            CompareColumn::MarketCap => row.market_cap,
+            CompareColumn::Pe => row.pe,
+            CompareColumn::GrossMargin => row.gross_margin,
+            CompareColumn::OperatingMargin => row.operating_margin,
+            CompareColumn::NetMargin => row.net_margin,
+            CompareColumn::Beta => row.beta,
+            CompareColumn::Return => row.week_52_return,
+        }
+    }
+
+    /// Missing numbers sort below every real one
+    fn compare(self, a: &PeerMetrics, b: &PeerMetrics) -> Ordering {
+        match self {
+            CompareColumn::Symbol => a.symbol.cmp(&b.symbol),
+            CompareColumn::Name => a.name.cmp(&b.name),
+            column => match (column.value(a), column.value(b)) {
+                (Some(a), Some(b)) => a.total_cmp(&b),
+                (a, b) => a.is_some().cmp(&b.is_some()),
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for CompareColumn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let output = match self {
+            CompareColumn::Symbol => "Symbol",
+            CompareColumn::Name => "Name",
+            CompareColumn::MarketCap => "Mkt cap",
+            CompareColumn::Pe => "P/E",
+            CompareColumn::GrossMargin => "Gross%",
+            CompareColumn::OperatingMargin => "Oper%",
+            CompareColumn::NetMargin => "Net%",
+            CompareColumn::Beta => "Beta",
+            CompareColumn::Return => "52w ret",
+        };
+        write!(f, "{}", output)
+    }
+}
+
+/// Market cap comes in millions: 2.96T, 845.2B, 950.0M
+fn market_cap(value: Option<f64>) -> String {
+    match value {
+        Some(value) if value >= 1e6 => format!("{:.2}T", value / 1e6),
+        Some(value) if value >= 1e3 => format!("{:.1}B", value / 1e3),
+        Some(value) => format!("{value:.1}M"),
+        None => "-".to_string(),
+    }
+}
+
+fn number(value: Option<f64>) -> String {
+    value.map_or("-".to_string(), |value| format!("{value:.2}"))
+}
+
+/// Kept in State so the sort and selection survive redraws
+#[derive(Debug, Clone)]
+pub struct ComparisonTable {
+    pub symbol: String,
+    pub rows: Vec<PeerMetrics>,
+    pub sort: CompareColumn,
+    pub descending: bool,
+    pub selected: usize,
+}
+
+impl ComparisonTable {
+    /// Biggest company first
+    pub fn new(symbol: String, rows: Vec<PeerMetrics>) -> Self {
+        Self {
+            symbol,
+            rows,
+            sort: CompareColumn::MarketCap,
+            descending: true,
+            selected: 0,
+        }
+    }
+
+    pub fn visible(&self) -> Vec<&PeerMetrics> {
+        let mut visible = self.rows.iter().collect::<Vec<_>>();
+        visible.sort_by(|a, b| {
+            let ordering = self.sort.compare(a, b);
+            if self.descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+        visible
+    }
+
+    /// s sorts by the next column and r reverses the order
+    pub fn key(&mut self, c: char) {
+        match c {
+            's' => self.sort = self.sort.next(),
+            'r' => self.descending = !self.descending,
+            _ => return,
+        }
+        self.selected = 0;
+    }
+
+    pub fn up(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn down(&mut self) {
+        if self.selected + 1 < self.rows.len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn render<B: Backend>(&self, f: &mut Frame<B>, area: Rect, block: Block) {
+        let visible = self.visible();
+        let rows = visible.iter().map(|row| {
+            let name = match (&row.name, &row.error) {
+                (Some(name), _) => name.clone(),
+                (None, Some(error)) => error.clone(),
+                (None, None) => "-".to_string(),
+            };
+            let percent = |value: Option<f64>| value.map_or("-".to_string(), |v| format!("{v:.1}"));
+            let style = if row.symbol == self.symbol {
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD)
+            } else if row.is_empty() {
+                Style::default().fg(Color::DarkGray)
+            } else {
+                Style::default()
+            };
+            Row::new(vec![
+                row.symbol.clone(),
+                name,
+                market_cap(row.market_cap),
+                number(row.pe),
+                percent(row.gross_margin),
+                percent(row.operating_margin),
+                percent(row.net_margin),
+                number(row.beta),
+                row.week_52_return
+                    .map_or("-".to_string(), |v| format!("{v:+.1}%")),
+            ])
+            .style(style)
+        });
+        // The sorted column gets an arrow for which way it's sorted
+        let header = CompareColumn::ALL.iter().map(|column| {
+            if *column == self.sort {
+                format!("{column} {}", if self.descending { "▼" } else { "▲" })
+            } else {
+                column.to_string()
+            }
+        });
+        let table = Table::new(rows)
+            .header(Row::new(header).style(Style::default().add_modifier(Modifier::BOLD)))
+            .block(block)
+            .style(Style::default().fg(Color::White).bg(Color::Black))
+            .widths(&[
+                Constraint::Length(6),
+                Constraint::Min(14),
+                Constraint::Length(9),
+                Constraint::Length(7),
+                Constraint::Length(8),
+                Constraint::Length(7),
+                Constraint::Length(6),
+                Constraint::Length(6),
+                Constraint::Length(9),
+            ])
+            .column_spacing(1)
+            .highlight_style(Style::default().bg(Color::DarkGray))
+            .highlight_symbol(">>");
+        let mut table_state = TableState::default();
+        if !visible.is_empty() {
+            table_state.select(Some(self.selected));
+        }
+        f.render_stateful_widget(table, area, &mut table_state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbols(set: &PeerSet) -> Vec<&str> {
+        match set {
+            PeerSet::PeersOf(symbol) => vec![symbol],
+            PeerSet::Symbols(symbols) => symbols.iter().map(String::as_str).collect(),
+        }
+    }
+
+    #[test]
+    fn one_symbol_is_peers_of_it() {
+        let set = PeerSet::parse(" aapl ", Some("MSFT")).unwrap();
+        assert!(matches!(set, PeerSet::PeersOf(_)));
+        assert_eq!(set.main_symbol(), "AAPL");
+    }
+
+    #[test]
+    fn nothing_typed_uses_the_current_symbol() {
+        let set = PeerSet::parse("", Some("MSFT")).unwrap();
+        assert!(matches!(set, PeerSet::PeersOf(_)));
+        assert_eq!(set.main_symbol(), "MSFT");
+        assert!(PeerSet::parse("", None).is_err());
+    }
+
+    #[test]
+    fn several_symbols_are_compared_without_repeats() {
+        let set = PeerSet::parse("aapl MSFT AAPL googl", None).unwrap();
+        assert!(matches!(set, PeerSet::Symbols(_)));
+        assert_eq!(symbols(&set), ["AAPL", "MSFT", "GOOGL"]);
+        assert_eq!(set.main_symbol(), "AAPL");
+        // Typing the same symbol twice is still just that symbol
+        assert!(matches!(
+            PeerSet::parse("AAPL aapl", None).unwrap(),
+            PeerSet::PeersOf(_)
+        ));
+    }
+}
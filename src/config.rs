@@ -0,0 +1,212 @@
+//! Settings that are read at startup instead of being baked into the binary.
+
+use std::{collections::HashMap, path::PathBuf};
+
+use anyhow::{Context, Error};
+use serde::{Deserialize, Serialize};
+
+use crate::import::ImportProfile;
+
+/// Environment variable checked for the Finnhub key when there's no --api-key flag
+pub const API_KEY_ENV: &str = "FINNHUB_API_KEY";
+
+/// Command line flags
+#[derive(Debug, Default)]
+pub struct Args {
+    /// --api-key <key>
+    pub api_key: Option<String>,
+    /// --fixtures [dir]: read replies from JSON files instead of Finnhub, from ./fixtures
+    /// without a dir
+    pub fixtures: Option<PathBuf>,
+}
+
+impl Args {
+    /// A flag that isn't known or --api-key without a key is an error, so a typo doesn't
+    /// quietly start the tool with the wrong settings
+    pub fn parse(args: impl Iterator<Item = String>) -> Result<Self, Error> {
+        let mut parsed = Args::default();
+        let mut args = args.skip(1).peekable();
+        while let Some(arg) = args.next() {
+            // What follows a flag is its value unless it's another flag
+            let value = args.next_if(|next| !next.starts_with("--"));
+            match arg.as_str() {
+                "--api-key" => match value {
+                    Some(key) => parsed.api_key = Some(key),
+                    None => {
+                        return Err(anyhow::anyhow!(
+                            "--api-key needs a key after it, e.g. --api-key <key>"
+                        ))
+                    }
+                },
+                "--fixtures" => {
+                    parsed.fixtures = Some(value.unwrap_or_else(|| "fixtures".into()).into())
+                }
+                other => return Err(anyhow::anyhow!(format!(
+                    "Unknown argument {other}, the flags are --api-key <key> and --fixtures [dir]"
+                ))),
+            }
+        }
+        Ok(parsed)
+    }
+}
+
+/// config.json in the config directory, e.g. ~/.config/finance-tool/config.json
+///
+/// {
+///   "api_key": "your key from https://finnhub.io",
+///   "cache_ttl": { "/quote": 5, "/stock/symbol": 86400 },
+///   "calls_per_minute": 60
+/// }
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub api_key: Option<String>,
+    /// Seconds to keep replies for each endpoint, on top of the defaults in cache.rs. 0 turns caching off
+    pub cache_ttl: HashMap<String, u64>,
+    /// For paid Finnhub plans, defaults to the free plan's 60
+    pub calls_per_minute: Option<u32>,
+    /// Column mappings for importing broker statements, see import.rs
+    pub import_profiles: HashMap<String, ImportProfile>,
+}
+
+impl Config {
+    /// A missing file just means the defaults, but a file that's there and broken is an error
+    pub fn load() -> Result<Self, Error> {
+        let Some(path) = config_file() else {
+            return Ok(Self::default());
+        };
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let text = std::fs::read_to_string(&path)
+            .with_context(|| format!("Couldn't read config file {}", path.display()))?;
+        serde_json::from_str(&text)
+            .with_context(|| format!("Couldn't understand config file {}", path.display()))
+    }
+}
+
+/// $XDG_CONFIG_HOME/finance-tool, or ~/.config/finance-tool if that isn't set
+pub fn config_dir() -> Option<PathBuf> {
+    let base = match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+    };
+    Some(base.join("finance-tool"))
+}
+
+/// $XDG_CACHE_HOME/finance-tool, or ~/.cache/finance-tool if that isn't set
+pub fn cache_dir() -> Option<PathBuf> {
+    let base = match std::env::var_os("XDG_CACHE_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(".cache"),
+    };
+    Some(base.join("finance-tool"))
+}
+
+/// $XDG_DATA_HOME/finance-tool, or ~/.local/share/finance-tool if that isn't set
+pub fn data_dir() -> Option<PathBuf> {
+    let base = match std::env::var_os("XDG_DATA_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(".local/share"),
+    };
+    Some(base.join("finance-tool"))
+}
+
+pub fn config_file() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("config.json"))
+}
+
+/// Looks for the key in this order: --api-key flag, FINNHUB_API_KEY, then config.json
+pub fn resolve_api_key(args: &Args, config: &Config) -> Result<String, Error> {
+    let from_env = std::env::var(API_KEY_ENV).ok();
+    let api_key = [args.api_key.as_ref(), from_env.as_ref(), config.api_key.as_ref()]
+        .into_iter()
+        .flatten()
+        .map(|key| key.trim())
+        .find(|key| !key.is_empty())
+        .map(str::to_string)
+        .ok_or_else(|| {
+            let config_file = config_file()
+                .map(|path| path.display().to_string())
+                .unwrap_or_else(|| "config.json in your config directory".into());
+            anyhow::anyhow!(format!(
+                "No Finnhub API key found. Get one on https://finnhub.io/ and then either\n\
+                 run with --api-key <key>, set {API_KEY_ENV}, or put {{\"api_key\": \"<key>\"}} in {config_file}"
+            ))
+        });
+    api_key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Args {
+        Args::parse(line.split_whitespace().map(String::from)).unwrap()
+    }
+
+    #[test]
+    fn parse_reads_the_flags() {
+        let parsed = args("finance-tool --api-key abc --fixtures data");
+        assert_eq!(parsed.api_key.as_deref(), Some("abc"));
+        assert_eq!(parsed.fixtures, Some(PathBuf::from("data")));
+        assert_eq!(
+            args("finance-tool --fixtures").fixtures,
+            Some(PathBuf::from("fixtures"))
+        );
+        assert!(args("finance-tool").api_key.is_none());
+    }
+
+    #[test]
+    fn fixtures_without_a_dir_leaves_the_next_flag_alone() {
+        let parsed = args("finance-tool --fixtures --api-key abc");
+        assert_eq!(parsed.fixtures, Some(PathBuf::from("fixtures")));
+        assert_eq!(parsed.api_key.as_deref(), Some("abc"));
+    }
+
+    #[test]
+    fn parse_rejects_unknown_flags_and_missing_keys() {
+        let parse = |line: &str| {
+            Args::parse(line.split_whitespace().map(String::from))
+                .unwrap_err()
+                .to_string()
+        };
+        assert!(parse("finance-tool --api-key").contains("--api-key needs a key"));
+        assert!(parse("finance-tool --api-key --fixtures").contains("--api-key needs a key"));
+        assert!(parse("finance-tool --apikey abc").contains("Unknown argument --apikey"));
+        assert!(parse("finance-tool abc").contains("Unknown argument abc"));
+    }
+
+    #[test]
+    fn config_fills_in_missing_fields() {
+        let config: Config = serde_json::from_str("{}").unwrap();
+        assert!(config.api_key.is_none());
+        assert!(serde_json::from_str::<Config>("{\"api_key\": 5}").is_err());
+    }
+
+    // Everything that reads FINNHUB_API_KEY is in this one test so they can't race
+    #[test]
+    fn key_comes_from_flag_then_env_then_config() {
+        let config: Config = serde_json::from_str("{\"api_key\": \"from-config\"}").unwrap();
+        std::env::set_var(API_KEY_ENV, "from-env");
+        assert_eq!(
+            resolve_api_key(&args("finance-tool --api-key from-flag"), &config).unwrap(),
+            "from-flag"
+        );
+        assert_eq!(
+            resolve_api_key(&args("finance-tool"), &config).unwrap(),
+            "from-env"
+        );
+
+        // A blank key doesn't count
+        std::env::set_var(API_KEY_ENV, "  ");
+        assert_eq!(
+            resolve_api_key(&args("finance-tool"), &config).unwrap(),
+            "from-config"
+        );
+
+        std::env::remove_var(API_KEY_ENV);
+        let error = resolve_api_key(&args("finance-tool"), &Config::default()).unwrap_err();
+        assert!(error.to_string().contains("No Finnhub API key found"));
+    }
+}
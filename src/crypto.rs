@@ -0,0 +1,76 @@
+//! Crypto: the exchanges Finnhub has and their symbols, searched the same way as the
+//! market's stock symbols. Candles come from Crypto Candles and use the Candles chart.
+
+use crate::api::CryptoSymbol;
+
+/// Every symbol whose pair, description or Finnhub symbol has needle in it, one per line
+pub fn search(symbols: &[CryptoSymbol], needle: &str) -> String {
+    let needle = needle.to_lowercase();
+    symbols
+        .iter()
+        .filter(|symbol| {
+            symbol.display_symbol.to_lowercase().contains(&needle)
+                || symbol.description.to_lowercase().contains(&needle)
+                || symbol.symbol.to_lowercase().contains(&needle)
+        })
+        .map(|symbol| format!("{} : {}\n", symbol.description, symbol.symbol))
+        .collect()
+}
+
+/// Finnhub wants the exchange in front, e.g. BINANCE:BTCUSDT, so BTCUSDT or BTC/USDT are
+/// looked up in the exchange that was loaded last. Anything else is sent as it was typed.
+pub fn resolve(symbols: &[CryptoSymbol], input: &str) -> String {
+    if input.contains(':') {
+        return input.to_string();
+    }
+    symbols
+        .iter()
+        .find(|symbol| {
+            symbol.display_symbol.eq_ignore_ascii_case(input)
+                || symbol
+                    .symbol
+                    .split_once(':')
+                    .is_some_and(|(_, pair)| pair.eq_ignore_ascii_case(input))
+        })
+        .map_or_else(|| input.to_string(), |symbol| symbol.symbol.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::provider::{FixtureProvider, MarketDataProvider};
+
+    fn symbols() -> Vec<CryptoSymbol> {
+        let fixtures = FixtureProvider::new(Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures"));
+        fixtures.crypto_symbols("binance").unwrap()
+    }
+
+    #[test]
+    fn search_matches_pair_description_or_symbol() {
+        let symbols = symbols();
+        assert_eq!(search(&symbols, "").lines().count(), 10);
+        assert_eq!(
+            search(&symbols, "eth"),
+            "Binance ETH/USDT : BINANCE:ETHUSDT\nBinance ETH/BTC : BINANCE:ETHBTC\n"
+        );
+        assert_eq!(
+            search(&symbols, "ltcbtc"),
+            "Binance LTC/BTC : BINANCE:LTCBTC\n"
+        );
+        assert_eq!(search(&symbols, "/btc").lines().count(), 3);
+        assert_eq!(search(&symbols, "kraken"), "");
+    }
+
+    #[test]
+    fn resolve_adds_the_exchange() {
+        let symbols = symbols();
+        assert_eq!(resolve(&symbols, "btc/usdt"), "BINANCE:BTCUSDT");
+        assert_eq!(resolve(&symbols, "ETHBTC"), "BINANCE:ETHBTC");
+        assert_eq!(resolve(&symbols, "KRAKEN:XBTUSD"), "KRAKEN:XBTUSD");
+        // Not in the exchange, so it goes out as typed
+        assert_eq!(resolve(&symbols, "PEPEUSDT"), "PEPEUSDT");
+        assert_eq!(resolve(&[], "BTCUSDT"), "BTCUSDT");
+    }
+}
@@ -0,0 +1,463 @@
+//! Earnings for the Results window: the calendar of upcoming and recent reports, with
+//! estimates next to actuals once a company has reported, and one company's EPS
+//! surprises over the last few quarters.
+
+use std::collections::HashSet;
+
+use chrono::{NaiveDate, Utc};
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    symbols::Marker,
+    text::Span,
+    widgets::{Axis, Block, Borders, Cell, Chart, Dataset, GraphType, Row, Table, TableState},
+    Frame,
+};
+
+use crate::{
+    api::{EarningsRelease, EarningsSurprise},
+    app::DateRange,
+};
+
+/// How far actual beat or missed the estimate, as a percentage of the estimate
+pub fn surprise(actual: Option<f64>, estimate: Option<f64>) -> Option<f64> {
+    match (actual, estimate) {
+        (Some(actual), Some(estimate)) if estimate != 0.0 => {
+            Some((actual - estimate) / estimate.abs() * 100.0)
+        }
+        _ => None,
+    }
+}
+
+/// 91.82B, 640.4M
+fn revenue(value: Option<f64>) -> String {
+    match value {
+        Some(value) if value.abs() >= 1e9 => format!("{:.2}B", value / 1e9),
+        Some(value) => format!("{:.1}M", value / 1e6),
+        None => "-".to_string(),
+    }
+}
+
+fn eps(value: Option<f64>) -> String {
+    value.map_or("-".to_string(), |value| format!("{value:.2}"))
+}
+
+/// Kept in State so the filter and selection survive redraws
+#[derive(Debug, Clone)]
+pub struct EarningsTable {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    // Soonest first, and before the open ahead of after the close on the same day
+    pub releases: Vec<EarningsRelease>,
+    // Everything on the watchlist or in the portfolio
+    pub mine: HashSet<String>,
+    pub only_mine: bool,
+    // Index into the filtered releases
+    pub selected: usize,
+}
+
+impl EarningsTable {
+    pub fn new(range: DateRange, mut releases: Vec<EarningsRelease>) -> Self {
+        let hour_order = |hour: &str| match hour {
+            "bmo" => 0,
+            "dmh" => 1,
+            "amc" => 2,
+            _ => 3,
+        };
+        releases.sort_by(|a, b| {
+            (&a.date, hour_order(&a.hour), &a.symbol).cmp(&(
+                &b.date,
+                hour_order(&b.hour),
+                &b.symbol,
+            ))
+        });
+        Self {
+            from: range.from,
+            to: range.to,
+            releases,
+            mine: HashSet::new(),
+            only_mine: false,
+            selected: 0,
+        }
+    }
+
+    /// Starts out showing only these if there are any
+    pub fn set_mine(&mut self, symbols: impl IntoIterator<Item = String>) {
+        self.mine = symbols.into_iter().collect();
+        self.only_mine = !self.mine.is_empty();
+        self.selected = 0;
+    }
+
+    pub fn visible(&self) -> Vec<&EarningsRelease> {
+        self.releases
+            .iter()
+            .filter(|release| !self.only_mine || self.mine.contains(&release.symbol))
+            .collect()
+    }
+
+    pub fn filter_name(&self) -> &str {
+        if self.only_mine {
+            "watchlist and portfolio"
+        } else {
+            "all symbols"
+        }
+    }
+
+    /// w switches between only the watchlist and portfolio symbols and everything
+    pub fn key(&mut self, c: char) {
+        if c == 'w' {
+            self.only_mine = !self.only_mine;
+            self.selected = 0;
+        }
+    }
+
+    pub fn up(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn down(&mut self) {
+        if self.selected + 1 < self.visible().len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn render<B: Backend>(&self, f: &mut Frame<B>, area: Rect, block: Block) {
+        let visible = self.visible();
+        let today = Utc::today().naive_utc().to_string();
+        let surprise_cell = |surprise: Option<f64>| match surprise {
+            Some(surprise) => {
+                let colour = if surprise >= 0.0 {
+                    Color::Green
+                } else {
+                    Color::Red
+                };
+                Cell::from(format!("{surprise:+.1}%")).style(Style::default().fg(colour))
+            }
+            None => Cell::from("-"),
+        };
+        let rows = visible.iter().map(|release| {
+            let row = Row::new(vec![
+                Cell::from(release.date.clone()),
+                Cell::from(release.hour.clone()),
+                Cell::from(release.symbol.clone()),
+                Cell::from(format!("{} Q{}", release.year, release.quarter)),
+                Cell::from(eps(release.eps_estimate)),
+                Cell::from(eps(release.eps_actual)),
+                surprise_cell(surprise(release.eps_actual, release.eps_estimate)),
+                Cell::from(revenue(release.revenue_estimate)),
+                Cell::from(revenue(release.revenue_actual)),
+                surprise_cell(surprise(release.revenue_actual, release.revenue_estimate)),
+            ]);
+            // What's already happened is greyed out so the week ahead stands out
+            if release.date < today {
+                row.style(Style::default().fg(Color::DarkGray))
+            } else {
+                row
+            }
+        });
+        let table = Table::new(rows)
+            .header(
+                Row::new(vec![
+                    "Date", "Hour", "Symbol", "Quarter", "EPS est", "EPS", "Surprise", "Rev est",
+                    "Revenue", "Surprise",
+                ])
+                .style(Style::default().add_modifier(Modifier::BOLD)),
+            )
+            .block(block)
+            .style(Style::default().fg(Color::White).bg(Color::Black))
+            .widths(&[
+                Constraint::Length(10),
+                Constraint::Length(4),
+                Constraint::Length(6),
+                Constraint::Length(7),
+                Constraint::Length(7),
+                Constraint::Length(6),
+                Constraint::Length(8),
+                Constraint::Length(8),
+                Constraint::Length(8),
+                Constraint::Length(8),
+            ])
+            .column_spacing(1)
+            .highlight_style(Style::default().bg(Color::Gray))
+            .highlight_symbol(">>");
+        let mut table_state = TableState::default();
+        if !visible.is_empty() {
+            table_state.select(Some(self.selected));
+        }
+        f.render_stateful_widget(table, area, &mut table_state);
+    }
+}
+
+/// Whether a quarter's EPS came in above, on or below the estimate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Beat,
+    Met,
+    Missed,
+}
+
+impl Outcome {
+    /// None until the quarter has both numbers
+    pub fn of(quarter: &EarningsSurprise) -> Option<Self> {
+        let (actual, estimate) = (quarter.actual?, quarter.estimate?);
+        Some(if actual > estimate {
+            Outcome::Beat
+        } else if actual < estimate {
+            Outcome::Missed
+        } else {
+            Outcome::Met
+        })
+    }
+}
+
+impl std::fmt::Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let output = match self {
+            Outcome::Beat => "beat",
+            Outcome::Met => "met",
+            Outcome::Missed => "missed",
+        };
+        write!(f, "{}", output)
+    }
+}
+
+/// Kept in State so it can be redrawn at whatever size the terminal currently is
+#[derive(Debug, Clone)]
+pub struct EpsHistory {
+    pub symbol: String,
+    // Oldest quarter first
+    pub quarters: Vec<EarningsSurprise>,
+}
+
+impl EpsHistory {
+    pub fn new(symbol: String, mut quarters: Vec<EarningsSurprise>) -> Self {
+        // The periods are YYYY-MM-DD so they sort as text
+        quarters.sort_by(|a, b| a.period.cmp(&b.period));
+        Self { symbol, quarters }
+    }
+
+    /// e.g. "beat 3 quarters in a row, beat 7 of 8"
+    pub fn streak(&self) -> String {
+        let outcomes = self
+            .quarters
+            .iter()
+            .rev()
+            .filter_map(Outcome::of)
+            .collect::<Vec<_>>();
+        let Some(latest) = outcomes.first() else {
+            return "no reported quarters".to_string();
+        };
+        let run = outcomes
+            .iter()
+            .take_while(|outcome| *outcome == latest)
+            .count();
+        let beats = outcomes
+            .iter()
+            .filter(|outcome| **outcome == Outcome::Beat)
+            .count();
+        let run = match run {
+            1 => format!("{latest} last quarter"),
+            run => format!("{latest} {run} quarters in a row"),
+        };
+        format!("{run}, beat {beats} of {}", outcomes.len())
+    }
+
+    pub fn render<B: Backend>(&self, f: &mut Frame<B>, area: Rect, block: Block) {
+        let inner = block.inner(area);
+        f.render_widget(block, area);
+        let chart_and_table = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+            .split(inner);
+
+        let points = |value: fn(&EarningsSurprise) -> Option<f64>| {
+            self.quarters
+                .iter()
+                .enumerate()
+                .filter_map(|(index, quarter)| Some((index as f64, value(quarter)?)))
+                .collect::<Vec<_>>()
+        };
+        let estimates = points(|quarter| quarter.estimate);
+        let beats = points(|quarter| match Outcome::of(quarter) {
+            Some(Outcome::Beat | Outcome::Met) => quarter.actual,
+            _ => None,
+        });
+        let misses = points(|quarter| match Outcome::of(quarter) {
+            Some(Outcome::Missed) => quarter.actual,
+            _ => None,
+        });
+        let y_bounds = match estimates
+            .iter()
+            .chain(&beats)
+            .chain(&misses)
+            .map(|(_, v)| (*v, *v))
+            .reduce(|(min, max), (v, _)| (min.min(v), max.max(v)))
+        {
+            Some((min, max)) => {
+                // Some room above and below so the dots aren't on the border
+                let padding = ((max - min) * 0.1).max(0.05);
+                [min - padding, max + padding]
+            }
+            // Nothing to draw yet, e.g. only upcoming quarters
+            None => [-1.0, 1.0],
+        };
+        let x_bounds = [-0.5, self.quarters.len() as f64 - 0.5];
+
+        let datasets = vec![
+            Dataset::default()
+                .name("Estimate")
+                .marker(Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(Color::Gray))
+                .data(&estimates),
+            Dataset::default()
+                .name("Beat")
+                .marker(Marker::Block)
+                .graph_type(GraphType::Scatter)
+                .style(Style::default().fg(Color::Green))
+                .data(&beats),
+            Dataset::default()
+                .name("Missed")
+                .marker(Marker::Block)
+                .graph_type(GraphType::Scatter)
+                .style(Style::default().fg(Color::Red))
+                .data(&misses),
+        ];
+        let x_labels = [self.quarters.first(), self.quarters.last()]
+            .iter()
+            .flatten()
+            .map(|quarter| Span::raw(format!("{} Q{}", quarter.year, quarter.quarter)))
+            .collect::<Vec<_>>();
+        let y_labels = [y_bounds[0], y_bounds[1]]
+            .iter()
+            .map(|value| Span::raw(format!("{value:.2}")))
+            .collect::<Vec<_>>();
+        let chart = Chart::new(datasets)
+            .block(Block::default().title("EPS").borders(Borders::ALL))
+            .style(Style::default().fg(Color::White).bg(Color::Black))
+            .x_axis(
+                Axis::default()
+                    .style(Style::default().fg(Color::Gray))
+                    .bounds(x_bounds)
+                    .labels(x_labels),
+            )
+            .y_axis(
+                Axis::default()
+                    .style(Style::default().fg(Color::Gray))
+                    .bounds(y_bounds)
+                    .labels(y_labels),
+            );
+        f.render_widget(chart, chart_and_table[0]);
+
+        // Newest first
+        let rows = self.quarters.iter().rev().map(|quarter| {
+            let outcome = Outcome::of(quarter);
+            let colour = match outcome {
+                Some(Outcome::Beat) => Color::Green,
+                Some(Outcome::Missed) => Color::Red,
+                _ => Color::White,
+            };
+            Row::new(vec![
+                Cell::from(quarter.period.clone()),
+                Cell::from(format!("{} Q{}", quarter.year, quarter.quarter)),
+                Cell::from(eps(quarter.estimate)),
+                Cell::from(eps(quarter.actual)),
+                Cell::from(
+                    quarter
+                        .surprise
+                        .map_or("-".to_string(), |surprise| format!("{surprise:+.2}")),
+                )
+                .style(Style::default().fg(colour)),
+                Cell::from(
+                    quarter
+                        .surprise_percent
+                        .map_or("-".to_string(), |percent| format!("{percent:+.2}%")),
+                )
+                .style(Style::default().fg(colour)),
+                Cell::from(outcome.map_or(String::new(), |outcome| outcome.to_string())),
+            ])
+        });
+        let table = Table::new(rows)
+            .header(
+                Row::new(vec![
+                    "Period",
+                    "Quarter",
+                    "Estimate",
+                    "Actual",
+                    "Surprise",
+                    "Surprise %",
+                    "",
+                ])
+                .style(Style::default().add_modifier(Modifier::BOLD)),
+            )
+            .block(Block::default().borders(Borders::ALL))
+            .style(Style::default().fg(Color::White).bg(Color::Black))
+            .widths(&[
+                Constraint::Length(10),
+                Constraint::Length(7),
+                Constraint::Length(8),
+                Constraint::Length(8),
+                Constraint::Length(8),
+                Constraint::Length(10),
+                Constraint::Length(6),
+            ])
+            .column_spacing(1);
+        f.render_widget(table, chart_and_table[1]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quarter(period: &str, actual: Option<f64>, estimate: Option<f64>) -> EarningsSurprise {
+        EarningsSurprise {
+            symbol: "AAPL".to_string(),
+            period: period.to_string(),
+            year: 0,
+            quarter: 0,
+            actual,
+            estimate,
+            surprise: None,
+            surprise_percent: None,
+        }
+    }
+
+    #[test]
+    fn streak_counts_back_from_the_latest_quarter() {
+        // Out of order on purpose, new sorts them by period
+        let history = EpsHistory::new(
+            "AAPL".to_string(),
+            vec![
+                quarter("2022-09-30", Some(1.3), Some(1.2)),
+                quarter("2022-03-31", Some(1.0), Some(1.1)),
+                quarter("2022-12-31", Some(1.5), Some(1.4)),
+                quarter("2022-06-30", Some(1.2), Some(1.2)),
+                quarter("2023-03-31", None, Some(1.6)),
+            ],
+        );
+        assert_eq!(history.streak(), "beat 2 quarters in a row, beat 2 of 4");
+    }
+
+    #[test]
+    fn streak_of_one() {
+        let history = EpsHistory::new(
+            "AAPL".to_string(),
+            vec![
+                quarter("2022-09-30", Some(1.3), Some(1.2)),
+                quarter("2022-12-31", Some(1.1), Some(1.4)),
+            ],
+        );
+        assert_eq!(history.streak(), "missed last quarter, beat 1 of 2");
+    }
+
+    #[test]
+    fn streak_without_reported_quarters() {
+        let history = EpsHistory::new(
+            "AAPL".to_string(),
+            vec![quarter("2023-03-31", None, Some(1.6))],
+        );
+        assert_eq!(history.streak(), "no reported quarters");
+    }
+}
@@ -0,0 +1,231 @@
+//! SEC filings for the Results window: a list of a company's recent filings that can be
+//! narrowed down by form, with the selected filing's links underneath ready to copy.
+
+use std::io::Write;
+
+use anyhow::{Context, Error};
+use chrono::NaiveDate;
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Paragraph, Row, Table, TableState, Wrap},
+    Frame,
+};
+
+use crate::{api::SecFiling, app::SymbolRange};
+
+/// The forms f cycles through after showing all of them
+pub const FORMS: [&str; 5] = ["8-K", "10-K", "10-Q", "4", "S-1"];
+
+/// Kept in State so the filter and selection survive redraws
+#[derive(Debug, Clone)]
+pub struct FilingsList {
+    pub symbol: String,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    // Newest first
+    pub filings: Vec<SecFiling>,
+    // Index into FORMS, all forms when None
+    pub form: Option<usize>,
+    // Index into the filtered filings
+    pub selected: usize,
+    // Says what was just copied
+    pub message: Option<String>,
+}
+
+/// Puts text on the clipboard of whatever terminal is showing the tool with an OSC 52
+/// escape, so it works over SSH too. Some terminals need this turned on in their settings.
+pub fn copy_to_clipboard(text: &str) -> Result<(), Error> {
+    let mut stdout = std::io::stdout();
+    write!(stdout, "\x1b]52;c;{}\x07", base64(text.as_bytes()))
+        .and_then(|_| stdout.flush())
+        .context("Couldn't write to the terminal to copy")
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut output = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |n, (index, byte)| {
+            n | (*byte as u32) << (16 - 8 * index)
+        });
+        for index in 0..4 {
+            if index <= chunk.len() {
+                output.push(ALPHABET[(n >> (18 - 6 * index) & 63) as usize] as char);
+            } else {
+                output.push('=');
+            }
+        }
+    }
+    output
+}
+
+impl FilingsList {
+    pub fn new(range: SymbolRange, mut filings: Vec<SecFiling>) -> Self {
+        // "2020-02-27 06:14:21" sorts as text
+        filings.sort_by(|a, b| b.accepted_date.cmp(&a.accepted_date));
+        Self {
+            symbol: range.symbol,
+            from: range.from,
+            to: range.to,
+            filings,
+            form: None,
+            selected: 0,
+            message: None,
+        }
+    }
+
+    pub fn form_name(&self) -> &str {
+        match self.form {
+            Some(index) => FORMS[index],
+            None => "all forms",
+        }
+    }
+
+    /// Amendments like 10-K/A count as the form they amend
+    pub fn visible(&self) -> Vec<&SecFiling> {
+        self.filings
+            .iter()
+            .filter(|filing| match self.form {
+                Some(index) => {
+                    let form = FORMS[index];
+                    filing.form == form
+                        || filing
+                            .form
+                            .strip_prefix(form)
+                            .is_some_and(|rest| rest.starts_with('/'))
+                }
+                None => true,
+            })
+            .collect()
+    }
+
+    /// f picks the next form to show, r copies the report URL and c the filing index URL
+    pub fn key(&mut self, c: char) -> Result<(), Error> {
+        self.message = None;
+        match c {
+            'f' => {
+                self.form = match self.form {
+                    None => Some(0),
+                    Some(index) if index + 1 < FORMS.len() => Some(index + 1),
+                    Some(_) => None,
+                };
+                self.selected = 0;
+            }
+            'r' | 'c' => {
+                let visible = self.visible();
+                let filing = visible
+                    .get(self.selected)
+                    .with_context(|| format!("No {} filings to copy", self.form_name()))?;
+                let (name, url) = match c {
+                    'r' => ("report", &filing.report_url),
+                    _ => ("filing", &filing.filing_url),
+                };
+                if url.is_empty() {
+                    return Err(anyhow::anyhow!(format!(
+                        "Finnhub didn't send a {name} URL for {}",
+                        filing.access_number
+                    )));
+                }
+                copy_to_clipboard(url)?;
+                self.message = Some(format!("Copied the {name} URL"));
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    pub fn up(&mut self) {
+        self.message = None;
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn down(&mut self) {
+        self.message = None;
+        if self.selected + 1 < self.visible().len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn render<B: Backend>(&self, f: &mut Frame<B>, area: Rect, block: Block) {
+        let inner = block.inner(area);
+        f.render_widget(block, area);
+        let table_and_links = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(8)].as_ref())
+            .split(inner);
+
+        let visible = self.visible();
+        let rows = visible.iter().map(|filing| {
+            Row::new(vec![
+                filing.form.clone(),
+                // Finnhub's filed dates come with a time of 00:00:00 on the end
+                filing
+                    .filed_date
+                    .get(..10)
+                    .unwrap_or(&filing.filed_date)
+                    .to_string(),
+                filing.accepted_date.clone(),
+                filing.access_number.clone(),
+            ])
+        });
+        let table = Table::new(rows)
+            .header(
+                Row::new(vec!["Form", "Filed", "Accepted", "Access number"])
+                    .style(Style::default().add_modifier(Modifier::BOLD)),
+            )
+            .style(Style::default().fg(Color::White).bg(Color::Black))
+            .widths(&[
+                Constraint::Percentage(12),
+                Constraint::Percentage(18),
+                Constraint::Percentage(30),
+                Constraint::Percentage(36),
+            ])
+            .column_spacing(1)
+            .highlight_style(Style::default().bg(Color::Gray))
+            .highlight_symbol(">>");
+        let mut table_state = TableState::default();
+        if !visible.is_empty() {
+            table_state.select(Some(self.selected));
+        }
+        f.render_stateful_widget(table, table_and_links[0], &mut table_state);
+
+        let links = match visible.get(self.selected) {
+            Some(filing) => format!(
+                "Report: {}\nFiling: {}",
+                filing.report_url, filing.filing_url
+            ),
+            None => format!(
+                "No {} filings between {} and {}",
+                self.form_name(),
+                self.from,
+                self.to
+            ),
+        };
+        let title = self
+            .message
+            .clone()
+            .unwrap_or_else(|| "r copies the report URL, c the filing URL".to_string());
+        let links = Paragraph::new(links)
+            .block(Block::default().title(title).borders(Borders::ALL))
+            .style(Style::default().fg(Color::White).bg(Color::Black))
+            .wrap(Wrap { trim: false });
+        f.render_widget(links, table_and_links[1]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base64_pads_short_chunks() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+        assert_eq!(base64(&[0xff, 0xfe, 0xfd]), "//79");
+    }
+}
@@ -0,0 +1,316 @@
+//! Basic financials for the Results window: the headline metrics in groups, with any of
+//! Finnhub's annual or quarterly series drawn as a sparkline next to them.
+
+use std::collections::BTreeMap;
+
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Cell, Row, Sparkline, Table, TableState},
+    Frame,
+};
+
+use crate::api::{BasicFinancials, SeriesPoint};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Period {
+    Annual,
+    Quarterly,
+}
+
+impl std::fmt::Display for Period {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let output = match self {
+            Period::Annual => "annual",
+            Period::Quarterly => "quarterly",
+        };
+        write!(f, "{}", output)
+    }
+}
+
+/// Kept in State so the selected series survives redraws
+#[derive(Debug, Clone)]
+pub struct FinancialsView {
+    pub financials: BasicFinancials,
+    pub period: Period,
+    // Index into the series for the current period
+    pub selected: usize,
+}
+
+/// Blank when Finnhub didn't send the metric
+fn number(value: Option<f64>, suffix: &str) -> String {
+    match value {
+        Some(value) => format!("{value:.2}{suffix}"),
+        None => "-".to_string(),
+    }
+}
+
+impl FinancialsView {
+    pub fn new(financials: BasicFinancials) -> Self {
+        // Quarterly for anything that only has quarterly numbers
+        let period = if financials.series.annual.is_empty() {
+            Period::Quarterly
+        } else {
+            Period::Annual
+        };
+        Self {
+            financials,
+            period,
+            selected: 0,
+        }
+    }
+
+    pub fn series(&self) -> &BTreeMap<String, Vec<SeriesPoint>> {
+        match self.period {
+            Period::Annual => &self.financials.series.annual,
+            Period::Quarterly => &self.financials.series.quarterly,
+        }
+    }
+
+    /// Switches between annual and quarterly, staying on the same series if the other period has it
+    pub fn toggle_period(&mut self) {
+        let name = self.selected_series().map(|(name, _)| name.clone());
+        self.period = match self.period {
+            Period::Annual => Period::Quarterly,
+            Period::Quarterly => Period::Annual,
+        };
+        self.selected = name
+            .and_then(|name| self.series().keys().position(|key| *key == name))
+            .unwrap_or(0);
+    }
+
+    pub fn up(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn down(&mut self) {
+        if self.selected + 1 < self.series().len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn selected_series(&self) -> Option<(&String, &Vec<SeriesPoint>)> {
+        self.series().iter().nth(self.selected)
+    }
+
+    fn metric_rows(&self) -> Vec<Row<'_>> {
+        let metric = &self.financials.metric;
+        let with_date = |value: Option<f64>, date: &Option<String>| match date {
+            Some(date) => format!("{} ({date})", number(value, "")),
+            None => number(value, ""),
+        };
+        let groups = [
+            (
+                "Valuation",
+                vec![
+                    ("Market cap (M)", number(metric.market_capitalization, "")),
+                    ("P/E (TTM)", number(metric.pe_ttm, "")),
+                    ("P/B", number(metric.pb, "")),
+                    ("P/S (TTM)", number(metric.ps_ttm, "")),
+                    ("EPS (TTM)", number(metric.eps_ttm, "")),
+                    ("Dividend yield", number(metric.dividend_yield, "%")),
+                    ("Beta", number(metric.beta, "")),
+                ],
+            ),
+            (
+                "Margins (TTM)",
+                vec![
+                    ("Gross", number(metric.gross_margin_ttm, "%")),
+                    ("Operating", number(metric.operating_margin_ttm, "%")),
+                    ("Pretax", number(metric.pretax_margin_ttm, "%")),
+                    ("Net", number(metric.net_margin_ttm, "%")),
+                ],
+            ),
+            (
+                "Returns (TTM)",
+                vec![
+                    ("On equity", number(metric.roe_ttm, "%")),
+                    ("On assets", number(metric.roa_ttm, "%")),
+                    ("On investment", number(metric.roi_ttm, "%")),
+                ],
+            ),
+            (
+                "52-week range",
+                vec![
+                    (
+                        "High",
+                        with_date(metric.week_52_high, &metric.week_52_high_date),
+                    ),
+                    (
+                        "Low",
+                        with_date(metric.week_52_low, &metric.week_52_low_date),
+                    ),
+                    ("Price return", number(metric.week_52_return, "%")),
+                    (
+                        "10-day volume (M)",
+                        number(metric.ten_day_average_volume, ""),
+                    ),
+                ],
+            ),
+        ];
+
+        let mut rows = Vec::new();
+        for (group, metrics) in groups {
+            rows.push(
+                Row::new(vec![Cell::from(group)])
+                    .style(Style::default().add_modifier(Modifier::BOLD)),
+            );
+            rows.extend(
+                metrics
+                    .into_iter()
+                    .map(|(name, value)| Row::new(vec![format!("  {name}"), value])),
+            );
+        }
+        rows
+    }
+
+    pub fn render<B: Backend>(&self, f: &mut Frame<B>, area: Rect, block: Block) {
+        let inner = block.inner(area);
+        f.render_widget(block, area);
+
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+            .split(inner);
+        let series_and_sparkline = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+            .split(columns[1]);
+
+        let metrics = Table::new(self.metric_rows())
+            .style(Style::default().fg(Color::White).bg(Color::Black))
+            .widths(&[Constraint::Percentage(50), Constraint::Percentage(50)]);
+        f.render_widget(metrics, columns[0]);
+
+        let series_rows = self.series().iter().map(|(name, points)| {
+            let latest = points.iter().find_map(|point| point.v);
+            Row::new(vec![name.clone(), number(latest, "")])
+        });
+        let series_table = Table::new(series_rows)
+            .block(
+                Block::default()
+                    .title(format!("{} series", self.period))
+                    .borders(Borders::ALL),
+            )
+            .style(Style::default().fg(Color::White).bg(Color::Black))
+            .widths(&[Constraint::Percentage(65), Constraint::Percentage(35)])
+            .highlight_style(Style::default().bg(Color::Gray))
+            .highlight_symbol(">>");
+        let mut table_state = TableState::default();
+        if !self.series().is_empty() {
+            table_state.select(Some(self.selected));
+        }
+        f.render_stateful_widget(series_table, series_and_sparkline[0], &mut table_state);
+
+        let Some((name, points)) = self.selected_series() else {
+            let empty = Block::default()
+                .title(format!("No {} series", self.period))
+                .borders(Borders::ALL);
+            f.render_widget(empty, series_and_sparkline[1]);
+            return;
+        };
+        // Finnhub sends the newest period first, the sparkline wants it last
+        let points = points
+            .iter()
+            .rev()
+            .filter_map(|point| Some((point.period.as_str(), point.v?)))
+            .collect::<Vec<_>>();
+        // Only as many periods as there's room for, newest kept
+        let width = series_and_sparkline[1].width.saturating_sub(2) as usize;
+        let points = &points[points.len().saturating_sub(width)..];
+        let (min, max) = points
+            .iter()
+            .fold((f64::MAX, f64::MIN), |(min, max), (_, v)| {
+                (min.min(*v), max.max(*v))
+            });
+        // Bars start from the lowest value, which still gets a sliver so it doesn't vanish
+        let data = points
+            .iter()
+            .map(|(_, v)| {
+                if max > min {
+                    ((v - min) / (max - min) * 99.0) as u64 + 1
+                } else {
+                    50
+                }
+            })
+            .collect::<Vec<_>>();
+        let title = match (points.first(), points.last()) {
+            (Some((first, _)), Some((last, latest))) => {
+                format!("{name}: {latest:.2} (low {min:.2}, high {max:.2}, {first} to {last})")
+            }
+            _ => format!("{name}: no numbers"),
+        };
+        let sparkline = Sparkline::default()
+            .block(Block::default().title(title).borders(Borders::ALL))
+            .data(&data)
+            .max(100)
+            .style(Style::default().fg(Color::LightCyan));
+        f.render_widget(sparkline, series_and_sparkline[1]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::provider::{FixtureProvider, MarketDataProvider};
+
+    fn view() -> FinancialsView {
+        let fixtures = FixtureProvider::new(Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures"));
+        FinancialsView::new(fixtures.basic_financials("AAPL").unwrap())
+    }
+
+    fn selected_name(view: &FinancialsView) -> Option<&str> {
+        view.selected_series().map(|(name, _)| name.as_str())
+    }
+
+    #[test]
+    fn starts_on_annual_unless_there_is_only_quarterly() {
+        let mut view = view();
+        assert_eq!(view.period, Period::Annual);
+        assert_eq!(selected_name(&view), Some("currentRatio"));
+        view.financials.series.annual.clear();
+        assert_eq!(
+            FinancialsView::new(view.financials).period,
+            Period::Quarterly
+        );
+    }
+
+    #[test]
+    fn selection_stays_inside_the_series() {
+        let mut view = view();
+        view.up();
+        assert_eq!(view.selected, 0);
+        for _ in 0..20 {
+            view.down();
+        }
+        assert_eq!(selected_name(&view), Some("salesPerShare"));
+    }
+
+    #[test]
+    fn toggle_keeps_the_series_when_the_other_period_has_it() {
+        let mut view = view();
+        view.down();
+        view.down();
+        view.down();
+        assert_eq!(selected_name(&view), Some("netMargin"));
+        view.toggle_period();
+        assert_eq!(view.period, Period::Quarterly);
+        assert_eq!(selected_name(&view), Some("netMargin"));
+
+        view.toggle_period();
+        view.down();
+        assert_eq!(selected_name(&view), Some("pe"));
+        // There's no quarterly P/E, so it goes back to the first series
+        view.toggle_period();
+        assert_eq!(selected_name(&view), Some("currentRatio"));
+    }
+
+    #[test]
+    fn missing_numbers_are_a_dash() {
+        assert_eq!(number(Some(12.345), "%"), "12.35%");
+        assert_eq!(number(None, "%"), "-");
+    }
+}
@@ -0,0 +1,148 @@
+//! Forex: the exchanges and currency pairs Finnhub has, the latest rates and a converter
+//! typed into the search box as AMOUNT FROM TO.
+
+use anyhow::{Context, Error};
+
+use crate::api::{ForexRates, ForexSymbol};
+
+/// What the Forex Rates choice was asked for
+#[derive(Debug, Clone)]
+pub enum ForexRequest {
+    // Every rate against one currency
+    Rates(String),
+    Convert {
+        amount: f64,
+        from: String,
+        to: String,
+    },
+}
+
+impl ForexRequest {
+    /// BASE for its rates (USD without one), or AMOUNT FROM [to] TO, e.g. "100 USD EUR"
+    pub fn parse(input: &str) -> Result<Self, Error> {
+        let words = input
+            .split_whitespace()
+            .filter(|word| !word.eq_ignore_ascii_case("to"))
+            .collect::<Vec<_>>();
+        let currency = |code: &str| {
+            if code.len() == 3 && code.chars().all(|c| c.is_ascii_alphabetic()) {
+                Ok(code.to_uppercase())
+            } else {
+                Err(anyhow::anyhow!(format!(
+                    "{code} isn't a currency code, use three letters like USD"
+                )))
+            }
+        };
+        match words.as_slice() {
+            [] => Ok(ForexRequest::Rates("USD".to_string())),
+            [base] => Ok(ForexRequest::Rates(currency(base)?)),
+            [amount, from, to] => Ok(ForexRequest::Convert {
+                amount: amount
+                    .replace(',', "")
+                    .parse()
+                    .with_context(|| format!("Couldn't read {amount} as an amount"))?,
+                from: currency(from)?,
+                to: currency(to)?,
+            }),
+            _ => Err(anyhow::anyhow!(
+                "Type a currency for its rates (e.g. EUR), or AMOUNT FROM TO to convert, e.g. 100 USD EUR"
+            )),
+        }
+    }
+
+    /// The currency to ask Finnhub for rates against
+    pub fn base(&self) -> &str {
+        match self {
+            ForexRequest::Rates(base) => base,
+            ForexRequest::Convert { from, .. } => from,
+        }
+    }
+
+    /// What goes in the Results window once the rates are in
+    pub fn answer(&self, rates: &ForexRates) -> Result<String, Error> {
+        match self {
+            ForexRequest::Rates(base) => {
+                let mut output = format!("1 {base} is worth:\n\n");
+                for (currency, rate) in &rates.quote {
+                    if currency != base {
+                        output.push_str(&format!("{currency} {rate:.4}\n"));
+                    }
+                }
+                Ok(output)
+            }
+            ForexRequest::Convert { amount, from, to } => {
+                let rate = rates
+                    .rate(to)
+                    .with_context(|| format!("No rate from {from} to {to}"))?;
+                Ok(format!(
+                    "{amount:.2} {from} = {:.2} {to}\n\n1 {from} = {rate:.4} {to}\n1 {to} = {:.4} {from}",
+                    amount * rate,
+                    1.0 / rate
+                ))
+            }
+        }
+    }
+}
+
+/// One exchange's currency pairs, narrowed down to the ones that mention filter
+pub fn symbols_text(exchange: &str, symbols: &[ForexSymbol], filter: Option<&str>) -> String {
+    let filter = filter.map(str::to_uppercase);
+    let lines = symbols
+        .iter()
+        .filter(|symbol| {
+            filter.as_deref().is_none_or(|filter| {
+                symbol.display_symbol.to_uppercase().contains(filter)
+                    || symbol.description.to_uppercase().contains(filter)
+            })
+        })
+        .map(|symbol| {
+            format!(
+                "{} {} : {}\n",
+                symbol.display_symbol, symbol.description, symbol.symbol
+            )
+        })
+        .collect::<String>();
+    if lines.is_empty() {
+        format!(
+            "No {} pairs on {exchange}",
+            filter.as_deref().unwrap_or_default()
+        )
+    } else {
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rates_default_to_usd() {
+        assert!(
+            matches!(ForexRequest::parse("").unwrap(), ForexRequest::Rates(base) if base == "USD")
+        );
+        assert!(
+            matches!(ForexRequest::parse(" eur ").unwrap(), ForexRequest::Rates(base) if base == "EUR")
+        );
+    }
+
+    #[test]
+    fn convert_reads_amount_and_codes() {
+        let request = ForexRequest::parse("1,000.5 usd to eur").unwrap();
+        let ForexRequest::Convert { amount, from, to } = &request else {
+            panic!("expected a conversion, got {request:?}");
+        };
+        assert_eq!(*amount, 1000.5);
+        assert_eq!(from, "USD");
+        assert_eq!(to, "EUR");
+        assert_eq!(request.base(), "USD");
+    }
+
+    #[test]
+    fn parse_rejects_bad_input() {
+        assert!(ForexRequest::parse("EURO").is_err());
+        assert!(ForexRequest::parse("lots USD EUR").is_err());
+        assert!(ForexRequest::parse("100 USD").is_err());
+        assert!(ForexRequest::parse("100 USD E1R").is_err());
+    }
+}
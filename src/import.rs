@@ -0,0 +1,442 @@
+//! Reads broker CSV statements into portfolio transactions. Brokers all name their
+//! columns differently, so a profile says which column is which.
+
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+};
+
+use anyhow::{Context, Error};
+use chrono::NaiveDate;
+use csv::StringRecord;
+use serde::{Deserialize, Serialize};
+
+use crate::lots::{Side, Transaction};
+
+/// How many row errors get listed before the rest are just counted
+const MAX_REPORTED_ERRORS: usize = 20;
+
+/// Which header each field comes from. Headers are matched without caring about case.
+/// Profiles go in config.json under "import_profiles", e.g.
+///
+/// "import_profiles": {
+///   "mybroker": {
+///     "date": "Trade Date", "date_format": "%m/%d/%Y", "side": "Action",
+///     "symbol": "Ticker", "quantity": "Shares", "price": "Price", "fees": "Commission"
+///   }
+/// }
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ImportProfile {
+    pub date: String,
+    /// chrono format for the date column. Anything after the date, like a time, is ignored.
+    #[serde(default = "default_date_format")]
+    pub date_format: String,
+    /// Without a side column a negative quantity is a sell
+    #[serde(default)]
+    pub side: Option<String>,
+    pub symbol: String,
+    pub quantity: String,
+    pub price: String,
+    /// A statement without the fees or currency column is read as having no fees and
+    /// the symbol's own currency
+    #[serde(default)]
+    pub fees: Option<String>,
+    #[serde(default)]
+    pub currency: Option<String>,
+    /// What the side column says for buys and sells
+    #[serde(default = "default_buy_words")]
+    pub buy_words: Vec<String>,
+    #[serde(default = "default_sell_words")]
+    pub sell_words: Vec<String>,
+    #[serde(default = "default_delimiter")]
+    pub delimiter: char,
+}
+
+fn default_date_format() -> String {
+    "%Y-%m-%d".to_string()
+}
+
+fn default_buy_words() -> Vec<String> {
+    vec!["buy".into(), "bought".into(), "b".into()]
+}
+
+fn default_sell_words() -> Vec<String> {
+    vec!["sell".into(), "sold".into(), "s".into()]
+}
+
+fn default_delimiter() -> char {
+    ','
+}
+
+/// Profiles that work without any config. Ones in config.json with the same name replace these.
+pub fn builtin_profiles() -> HashMap<String, ImportProfile> {
+    let default = ImportProfile {
+        date: "date".into(),
+        date_format: default_date_format(),
+        side: Some("side".into()),
+        symbol: "symbol".into(),
+        quantity: "quantity".into(),
+        price: "price".into(),
+        fees: Some("fees".into()),
+        currency: Some("currency".into()),
+        buy_words: default_buy_words(),
+        sell_words: default_sell_words(),
+        delimiter: default_delimiter(),
+    };
+    HashMap::from([("default".to_string(), default)])
+}
+
+/// Where each field is in a row
+struct Columns {
+    date: usize,
+    side: Option<usize>,
+    symbol: usize,
+    quantity: usize,
+    price: usize,
+    fees: Option<usize>,
+    currency: Option<usize>,
+}
+
+impl Columns {
+    fn find(headers: &StringRecord, profile: &ImportProfile) -> Result<Self, Error> {
+        let find = |name: &str| {
+            headers
+                .iter()
+                .position(|header| header.trim().eq_ignore_ascii_case(name.trim()))
+                .ok_or_else(|| {
+                    anyhow::anyhow!(format!(
+                        "No {name} column, the headers are: {}",
+                        headers.iter().collect::<Vec<_>>().join(", ")
+                    ))
+                })
+        };
+        let find_optional = |name: &Option<String>| name.as_deref().map(find).transpose();
+        let find_if_there =
+            |name: &Option<String>| name.as_deref().and_then(|name| find(name).ok());
+        Ok(Self {
+            date: find(&profile.date)?,
+            side: find_optional(&profile.side)?,
+            symbol: find(&profile.symbol)?,
+            quantity: find(&profile.quantity)?,
+            price: find(&profile.price)?,
+            fees: find_if_there(&profile.fees),
+            currency: find_if_there(&profile.currency),
+        })
+    }
+}
+
+/// Reads every row of a statement, giving the transactions ids from first_id up.
+/// Any bad row fails the whole import so a statement never goes in half way.
+pub fn import_csv(
+    path: &Path,
+    profile: &ImportProfile,
+    first_id: u64,
+    known_symbols: &HashSet<String>,
+) -> Result<Vec<Transaction>, Error> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(profile.delimiter as u8)
+        .flexible(true)
+        .from_path(path)
+        .map_err(|e| anyhow::anyhow!(format!("Couldn't open {}: {e}", path.display())))?;
+    let headers = reader
+        .headers()
+        .map_err(|e| {
+            anyhow::anyhow!(format!(
+                "Couldn't read the headers of {}: {e}",
+                path.display()
+            ))
+        })?
+        .clone();
+    let columns = Columns::find(&headers, profile).map_err(|e| {
+        anyhow::anyhow!(format!(
+            "{} doesn't match the import profile. {e}",
+            path.display()
+        ))
+    })?;
+
+    let mut transactions = Vec::new();
+    let mut errors = Vec::new();
+    for (record, id) in reader.records().zip(first_id..) {
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                let row = e.position().map_or(0, |position| position.line());
+                errors.push(format!("Row {row}: {e}"));
+                continue;
+            }
+        };
+        if record.iter().all(|field| field.trim().is_empty()) {
+            continue;
+        }
+        let row = record.position().map_or(0, |position| position.line());
+        match parse_row(&record, &columns, profile, id, known_symbols) {
+            Ok(transaction) => transactions.push(transaction),
+            Err(e) => errors.push(format!("Row {row}: {e}")),
+        }
+    }
+
+    if !errors.is_empty() {
+        let mut message = format!(
+            "Couldn't import {}, {} rows have problems:\n",
+            path.display(),
+            errors.len()
+        );
+        for error in errors.iter().take(MAX_REPORTED_ERRORS) {
+            message.push_str(error);
+            message.push('\n');
+        }
+        if errors.len() > MAX_REPORTED_ERRORS {
+            message.push_str(&format!(
+                "...and {} more",
+                errors.len() - MAX_REPORTED_ERRORS
+            ));
+        }
+        return Err(anyhow::anyhow!(message));
+    }
+    if transactions.is_empty() {
+        return Err(anyhow::anyhow!(format!(
+            "No transactions in {}",
+            path.display()
+        )));
+    }
+    Ok(transactions)
+}
+
+fn parse_row(
+    record: &StringRecord,
+    columns: &Columns,
+    profile: &ImportProfile,
+    id: u64,
+    known_symbols: &HashSet<String>,
+) -> Result<Transaction, Error> {
+    let field = |index: usize| record.get(index).unwrap_or("").trim();
+
+    let date_text = field(columns.date);
+    // Statements often put a time after the date
+    let date = NaiveDate::parse_from_str(date_text, &profile.date_format)
+        .or_else(|_| {
+            let first_word = date_text.split_whitespace().next().unwrap_or("");
+            NaiveDate::parse_from_str(first_word, &profile.date_format)
+        })
+        .with_context(|| {
+            format!(
+                "Couldn't read date {date_text:?} as {}",
+                profile.date_format
+            )
+        })?;
+
+    let symbol = field(columns.symbol).to_uppercase();
+    if symbol.is_empty() {
+        return Err(anyhow::anyhow!("No symbol"));
+    }
+    if !known_symbols.contains(&symbol) {
+        return Err(anyhow::anyhow!(format!(
+            "{symbol} isn't in the symbol list for this market"
+        )));
+    }
+
+    let quantity = number(field(columns.quantity), "quantity")?;
+    let side = match columns.side {
+        Some(index) => {
+            let side = field(index).to_lowercase();
+            if profile
+                .buy_words
+                .iter()
+                .any(|word| word.to_lowercase() == side)
+            {
+                Side::Buy
+            } else if profile
+                .sell_words
+                .iter()
+                .any(|word| word.to_lowercase() == side)
+            {
+                Side::Sell
+            } else {
+                return Err(anyhow::anyhow!(format!(
+                    "Side {side:?} isn't one of {} or {}",
+                    profile.buy_words.join("/"),
+                    profile.sell_words.join("/")
+                )));
+            }
+        }
+        None if quantity < 0.0 => Side::Sell,
+        None => Side::Buy,
+    };
+    let fees = match columns.fees {
+        Some(index) if !field(index).is_empty() => number(field(index), "fees")?,
+        _ => 0.0,
+    };
+    let currency = columns
+        .currency
+        .map(|index| field(index).to_uppercase())
+        .filter(|currency| !currency.is_empty());
+
+    // Brokers write sells and fees as negative numbers as often as not
+    let quantity = quantity.abs();
+    if quantity == 0.0 {
+        return Err(anyhow::anyhow!("Quantity is 0"));
+    }
+    Ok(Transaction {
+        id,
+        date,
+        side,
+        symbol,
+        quantity,
+        price: number(field(columns.price), "price")?.abs(),
+        fees: fees.abs(),
+        lot: None,
+        currency,
+    })
+}
+
+/// Reads 1,234.50 and $12 as well as plain numbers
+fn number(text: &str, name: &str) -> Result<f64, Error> {
+    text.replace([',', '$'], "")
+        .parse()
+        .with_context(|| format!("Couldn't read {name} {text:?} as a number"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    /// A statement in the temp directory that's removed when the test is done with it
+    struct Statement(PathBuf);
+
+    impl Statement {
+        fn new(name: &str, text: &str) -> Self {
+            let path = std::env::temp_dir().join(format!(
+                "finance-tool-import-test-{}-{name}.csv",
+                std::process::id()
+            ));
+            std::fs::write(&path, text).unwrap();
+            Self(path)
+        }
+    }
+
+    impl Drop for Statement {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    fn known() -> HashSet<String> {
+        ["AAPL", "MSFT"].into_iter().map(String::from).collect()
+    }
+
+    fn default_profile() -> ImportProfile {
+        builtin_profiles().remove("default").unwrap()
+    }
+
+    #[test]
+    fn reads_the_default_profile() {
+        let statement = Statement::new(
+            "default",
+            "Date,Side,Symbol,Quantity,Price,Fees,Currency\n\
+             2023-01-05,buy,aapl,10,\"1,250.50\",1,eur\n\
+             ,,,,,,\n\
+             2023-02-01 15:30,SELL,MSFT,-5,$250,-2,\n",
+        );
+        let transactions = import_csv(&statement.0, &default_profile(), 10, &known()).unwrap();
+        assert_eq!(transactions.len(), 2);
+        let buy = &transactions[0];
+        assert_eq!(buy.id, 10);
+        assert_eq!(buy.side, Side::Buy);
+        assert_eq!(buy.symbol, "AAPL");
+        assert_eq!(buy.price, 1250.5);
+        assert_eq!(buy.currency.as_deref(), Some("EUR"));
+        let sell = &transactions[1];
+        assert_eq!(sell.id, 12);
+        assert_eq!(sell.side, Side::Sell);
+        assert_eq!(sell.date, NaiveDate::from_ymd_opt(2023, 2, 1).unwrap());
+        assert_eq!(sell.quantity, 5.0);
+        assert_eq!(sell.price, 250.0);
+        assert_eq!(sell.fees, 2.0);
+        assert_eq!(sell.currency, None);
+    }
+
+    #[test]
+    fn default_profile_reads_a_statement_without_fees_or_currency() {
+        let statement = Statement::new(
+            "plain",
+            "Date,Side,Symbol,Quantity,Price\n2023-01-05,buy,AAPL,10,125\n",
+        );
+        let transactions = import_csv(&statement.0, &default_profile(), 1, &known()).unwrap();
+        assert_eq!(transactions.len(), 1);
+        assert_eq!(transactions[0].fees, 0.0);
+        assert_eq!(transactions[0].currency, None);
+        // The side still has to be there, or every row would be read as a buy
+        let statement = Statement::new(
+            "no-side-column",
+            "Date,Symbol,Quantity,Price\n2023-01-05,AAPL,10,125\n",
+        );
+        let error = import_csv(&statement.0, &default_profile(), 1, &known())
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("No side column"));
+    }
+
+    #[test]
+    fn negative_quantity_is_a_sell_without_a_side_column() {
+        let profile = ImportProfile {
+            date: "Trade Date".into(),
+            date_format: "%m/%d/%Y".into(),
+            side: None,
+            symbol: "Ticker".into(),
+            quantity: "Shares".into(),
+            price: "Price".into(),
+            fees: None,
+            currency: None,
+            buy_words: default_buy_words(),
+            sell_words: default_sell_words(),
+            delimiter: ';',
+        };
+        let statement = Statement::new(
+            "no-side",
+            "Trade Date;Ticker;Shares;Price\n01/05/2023;AAPL;10;125\n02/01/2023;AAPL;-4;130\n",
+        );
+        let transactions = import_csv(&statement.0, &profile, 1, &known()).unwrap();
+        assert_eq!(transactions[0].side, Side::Buy);
+        assert_eq!(transactions[1].side, Side::Sell);
+        assert_eq!(transactions[1].quantity, 4.0);
+    }
+
+    #[test]
+    fn any_bad_row_fails_the_import() {
+        let statement = Statement::new(
+            "bad-rows",
+            "date,side,symbol,quantity,price,fees,currency\n\
+             2023-01-05,buy,AAPL,10,125,,\n\
+             2023-01-06,hold,AAPL,10,125,,\n\
+             2023-01-07,buy,TSLA,10,125,,\n\
+             2023-01-08,buy,AAPL,0,125,,\n",
+        );
+        let error = import_csv(&statement.0, &default_profile(), 1, &known())
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("3 rows have problems"));
+        assert!(error.contains("Row 3: Side \"hold\""));
+        assert!(error.contains("Row 4: TSLA isn't in the symbol list"));
+        assert!(error.contains("Row 5: Quantity is 0"));
+    }
+
+    #[test]
+    fn missing_column_names_the_headers() {
+        let statement = Statement::new(
+            "no-price",
+            "date,side,symbol,quantity\n2023-01-05,buy,AAPL,10\n",
+        );
+        let error = import_csv(&statement.0, &default_profile(), 1, &known())
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("No price column, the headers are: date, side, symbol, quantity"));
+    }
+
+    #[test]
+    fn empty_statement_fails() {
+        let statement = Statement::new("empty", "date,side,symbol,quantity,price,fees,currency\n");
+        assert!(import_csv(&statement.0, &default_profile(), 1, &known()).is_err());
+    }
+}
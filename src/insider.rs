@@ -0,0 +1,564 @@
+//! Insider activity for the Results window: the monthly share purchase ratio (MSPR)
+//! as a bar chart with the net shares bought or sold under it, and the insiders'
+//! own transactions as a table.
+
+use chrono::NaiveDate;
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    symbols::Marker,
+    text::Span,
+    widgets::{Axis, Block, Borders, Cell, Chart, Dataset, GraphType, Row, Table, TableState},
+    Frame,
+};
+
+use crate::{
+    api::{InsiderSentiment, InsiderTransaction},
+    app::SymbolRange,
+};
+
+/// Kept in State so it can be redrawn at whatever size the terminal currently is
+#[derive(Debug, Clone)]
+pub struct SentimentChart {
+    pub symbol: String,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    // Oldest month first
+    pub months: Vec<InsiderSentiment>,
+}
+
+impl SentimentChart {
+    pub fn new(range: SymbolRange, mut months: Vec<InsiderSentiment>) -> Self {
+        months.sort_by_key(|month| (month.year, month.month));
+        Self {
+            symbol: range.symbol,
+            from: range.from,
+            to: range.to,
+            months,
+        }
+    }
+
+    fn label(month: &InsiderSentiment) -> String {
+        format!("{}-{:02}", month.year, month.month)
+    }
+
+    pub fn render<B: Backend>(&self, f: &mut Frame<B>, area: Rect, block: Block) {
+        let inner = block.inner(area);
+        f.render_widget(block, area);
+        let chart_and_table = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Percentage(60), Constraint::Percentage(40)].as_ref())
+            .split(inner);
+
+        // Symmetric around 0 so buying and selling months look the same size
+        let extent = self
+            .months
+            .iter()
+            .map(|month| month.mspr.abs())
+            .fold(1.0, f64::max)
+            * 1.1;
+        let y_bounds = [-extent, extent];
+        let x_bounds = [-1.0, self.months.len() as f64];
+
+        // Each bar is a zigzag between 0 and the MSPR, which fills in as a solid block
+        let bar_points = self
+            .months
+            .iter()
+            .enumerate()
+            .map(|(index, month)| {
+                (0..=6)
+                    .flat_map(|step| {
+                        let x = index as f64 - 0.3 + step as f64 * 0.1;
+                        if step % 2 == 0 {
+                            [(x, 0.0), (x, month.mspr)]
+                        } else {
+                            [(x, month.mspr), (x, 0.0)]
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let zero_line = [(x_bounds[0], 0.0), (x_bounds[1], 0.0)];
+
+        let mut datasets = vec![Dataset::default()
+            .marker(Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(Color::Gray))
+            .data(&zero_line)];
+        datasets.extend(
+            self.months
+                .iter()
+                .zip(bar_points.iter())
+                .map(|(month, points)| {
+                    let colour = if month.mspr >= 0.0 {
+                        Color::Green
+                    } else {
+                        Color::Red
+                    };
+                    Dataset::default()
+                        .marker(Marker::Block)
+                        .graph_type(GraphType::Line)
+                        .style(Style::default().fg(colour))
+                        .data(points)
+                }),
+        );
+
+        let x_label_count = (inner.width as usize / 12).clamp(2, 8);
+        let x_labels = (0..x_label_count)
+            .filter_map(|n| {
+                let index = n * self.months.len().saturating_sub(1) / (x_label_count - 1);
+                self.months
+                    .get(index)
+                    .map(|month| Span::raw(Self::label(month)))
+            })
+            .collect::<Vec<_>>();
+        let y_labels = [-extent, 0.0, extent]
+            .iter()
+            .map(|value| Span::raw(format!("{value:.0}")))
+            .collect::<Vec<_>>();
+
+        let chart = Chart::new(datasets)
+            .block(Block::default().title("MSPR").borders(Borders::ALL))
+            .style(Style::default().fg(Color::White).bg(Color::Black))
+            .x_axis(
+                Axis::default()
+                    .style(Style::default().fg(Color::Gray))
+                    .bounds(x_bounds)
+                    .labels(x_labels),
+            )
+            .y_axis(
+                Axis::default()
+                    .style(Style::default().fg(Color::Gray))
+                    .bounds(y_bounds)
+                    .labels(y_labels),
+            );
+        f.render_widget(chart, chart_and_table[0]);
+
+        let colour = |number: f64| {
+            Style::default().fg(if number >= 0.0 {
+                Color::Green
+            } else {
+                Color::Red
+            })
+        };
+        let total_change = self.months.iter().map(|month| month.change).sum::<f64>();
+        let average_mspr =
+            self.months.iter().map(|month| month.mspr).sum::<f64>() / self.months.len() as f64;
+        let mut rows = vec![Row::new(vec![
+            Cell::from("All"),
+            Cell::from(format!("{average_mspr:+.2} avg")).style(colour(average_mspr)),
+            Cell::from(format!("{total_change:+}")).style(colour(total_change)),
+        ])
+        .style(Style::default().add_modifier(Modifier::BOLD))];
+        // Newest first, so it's the oldest months that don't fit
+        rows.extend(self.months.iter().rev().map(|month| {
+            Row::new(vec![
+                Cell::from(Self::label(month)),
+                Cell::from(format!("{:+.2}", month.mspr)).style(colour(month.mspr)),
+                Cell::from(format!("{:+}", month.change)).style(colour(month.change)),
+            ])
+        }));
+        let table = Table::new(rows)
+            .header(
+                Row::new(vec!["Month", "MSPR", "Net shares"])
+                    .style(Style::default().add_modifier(Modifier::BOLD)),
+            )
+            .block(Block::default().borders(Borders::ALL))
+            .style(Style::default().fg(Color::White).bg(Color::Black))
+            .widths(&[
+                Constraint::Length(9),
+                Constraint::Length(12),
+                Constraint::Min(12),
+            ])
+            .column_spacing(1);
+        f.render_widget(table, chart_and_table[1]);
+    }
+}
+
+/// Which transactions the table shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SideFilter {
+    All,
+    /// Open market or private purchases, code P. Awards (A) and option exercises (M) add
+    /// shares too but weren't bought.
+    Buys,
+    /// Open market or private sales, code S
+    Sells,
+}
+
+#[derive(Debug, Clone)]
+pub struct InsiderFilter {
+    pub side: SideFilter,
+    pub insider: Option<String>,
+    // One Form 4 transaction code, e.g. M for option exercises
+    pub code: Option<String>,
+}
+
+impl std::fmt::Display for InsiderFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let side = match self.side {
+            SideFilter::All => "all",
+            SideFilter::Buys => "buys only",
+            SideFilter::Sells => "sells only",
+        };
+        write!(f, "{side}")?;
+        if let Some(code) = &self.code {
+            write!(f, ", code {code}")?;
+        }
+        if let Some(insider) = &self.insider {
+            write!(f, " by {insider}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Kept in State so the filter and selection survive redraws
+#[derive(Debug, Clone)]
+pub struct InsiderTable {
+    pub symbol: String,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    // Newest first
+    pub transactions: Vec<InsiderTransaction>,
+    pub filter: InsiderFilter,
+    // Index into the filtered transactions
+    pub selected: usize,
+}
+
+/// Net change, value and priced shares for one insider's transactions, and how many there were
+#[derive(Debug, Default)]
+struct InsiderTotal {
+    change: f64,
+    value: f64,
+    priced_shares: f64,
+    count: usize,
+}
+
+impl InsiderTable {
+    pub fn new(range: SymbolRange, mut transactions: Vec<InsiderTransaction>) -> Self {
+        // The dates are YYYY-MM-DD so they sort as text
+        transactions.sort_by(|a, b| b.transaction_date.cmp(&a.transaction_date));
+        Self {
+            symbol: range.symbol,
+            from: range.from,
+            to: range.to,
+            transactions,
+            filter: InsiderFilter {
+                side: SideFilter::All,
+                insider: None,
+                code: None,
+            },
+            selected: 0,
+        }
+    }
+
+    pub fn visible(&self) -> Vec<&InsiderTransaction> {
+        self.transactions
+            .iter()
+            .filter(|transaction| match self.filter.side {
+                SideFilter::All => true,
+                SideFilter::Buys => transaction.transaction_code == "P",
+                SideFilter::Sells => transaction.transaction_code == "S",
+            })
+            .filter(|transaction| {
+                self.filter
+                    .code
+                    .as_ref()
+                    .is_none_or(|code| transaction.transaction_code == *code)
+            })
+            .filter(|transaction| {
+                self.filter
+                    .insider
+                    .as_ref()
+                    .is_none_or(|insider| transaction.name == *insider)
+            })
+            .collect()
+    }
+
+    /// Every transaction code in the data, in alphabetical order
+    pub fn codes(&self) -> Vec<&str> {
+        let mut codes = self
+            .transactions
+            .iter()
+            .map(|transaction| transaction.transaction_code.as_str())
+            .filter(|code| !code.is_empty())
+            .collect::<Vec<_>>();
+        codes.sort();
+        codes.dedup();
+        codes
+    }
+
+    /// The code after the current one, then back to every code after the last
+    fn next_code(&self) -> Option<String> {
+        let codes = self.codes();
+        let next = match &self.filter.code {
+            None => codes.first(),
+            Some(code) => codes
+                .iter()
+                .position(|c| c == code)
+                .and_then(|index| codes.get(index + 1)),
+        };
+        next.map(|code| code.to_string())
+    }
+
+    /// a shows everything, b buys only, s sells only, c cycles through the transaction codes
+    /// and i shows only the selected insider (again to undo)
+    pub fn key(&mut self, c: char) {
+        let selected_name = self
+            .visible()
+            .get(self.selected)
+            .map(|transaction| transaction.name.clone());
+        match c {
+            'a' => {
+                self.filter.side = SideFilter::All;
+                self.filter.insider = None;
+                self.filter.code = None;
+            }
+            'b' => self.filter.side = SideFilter::Buys,
+            's' => self.filter.side = SideFilter::Sells,
+            'c' => self.filter.code = self.next_code(),
+            'i' => {
+                self.filter.insider = match self.filter.insider {
+                    Some(_) => None,
+                    None => selected_name,
+                }
+            }
+            _ => return,
+        }
+        self.selected = 0;
+    }
+
+    pub fn up(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn down(&mut self) {
+        if self.selected + 1 < self.visible().len() {
+            self.selected += 1;
+        }
+    }
+
+    /// One per insider, biggest net change first
+    fn totals<'a>(visible: &[&'a InsiderTransaction]) -> Vec<(&'a str, InsiderTotal)> {
+        let mut totals: Vec<(&str, InsiderTotal)> = Vec::new();
+        for transaction in visible {
+            let index = match totals
+                .iter()
+                .position(|(name, _)| *name == transaction.name)
+            {
+                Some(index) => index,
+                None => {
+                    totals.push((&transaction.name, InsiderTotal::default()));
+                    totals.len() - 1
+                }
+            };
+            let total = &mut totals[index].1;
+            total.change += transaction.change;
+            total.count += 1;
+            // Awards and gifts come through at a price of 0 and would drag the average down
+            if transaction.transaction_price > 0.0 {
+                total.value += transaction.change.abs() * transaction.transaction_price;
+                total.priced_shares += transaction.change.abs();
+            }
+        }
+        totals.sort_by(|a, b| b.1.change.abs().total_cmp(&a.1.change.abs()));
+        totals
+    }
+
+    pub fn render<B: Backend>(&self, f: &mut Frame<B>, area: Rect, block: Block) {
+        let visible = self.visible();
+        let colour = |number: f64| {
+            Style::default().fg(if number >= 0.0 {
+                Color::Green
+            } else {
+                Color::Red
+            })
+        };
+
+        // The totals go above the transactions so they're on screen however many there are
+        let mut rows = Self::totals(&visible)
+            .into_iter()
+            .map(|(name, total)| {
+                let average_price = if total.priced_shares > 0.0 {
+                    total.value / total.priced_shares
+                } else {
+                    0.0
+                };
+                Row::new(vec![
+                    Cell::from(format!("Total {name}")),
+                    Cell::from(format!("{:+}", total.change)).style(colour(total.change)),
+                    Cell::from(format!("{average_price:.2}")),
+                    Cell::from(format!("{} trades", total.count)),
+                    Cell::from(""),
+                ])
+                .style(Style::default().add_modifier(Modifier::BOLD))
+            })
+            .collect::<Vec<_>>();
+        let total_rows = rows.len();
+        rows.extend(visible.iter().map(|transaction| {
+            Row::new(vec![
+                Cell::from(transaction.name.clone()),
+                Cell::from(format!("{:+}", transaction.change)).style(colour(transaction.change)),
+                Cell::from(format!("{:.2}", transaction.transaction_price)),
+                Cell::from(transaction.transaction_date.clone()),
+                Cell::from(transaction.transaction_code.clone()),
+            ])
+        }));
+
+        let table = Table::new(rows)
+            .header(
+                Row::new(vec!["Name", "Change", "Price", "Date", "Code"])
+                    .style(Style::default().add_modifier(Modifier::BOLD)),
+            )
+            .block(block)
+            .style(Style::default().fg(Color::White).bg(Color::Black))
+            .widths(&[
+                Constraint::Percentage(38),
+                Constraint::Percentage(15),
+                Constraint::Percentage(12),
+                Constraint::Percentage(14),
+                Constraint::Percentage(6),
+            ])
+            .column_spacing(1)
+            .highlight_style(Style::default().bg(Color::Gray))
+            .highlight_symbol(">>");
+
+        let mut table_state = TableState::default();
+        if !visible.is_empty() {
+            table_state.select(Some(total_rows + self.selected));
+        }
+        f.render_stateful_widget(table, area, &mut table_state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::provider::{FixtureProvider, MarketDataProvider};
+
+    fn month(year: i32, month: u32, mspr: f64) -> InsiderSentiment {
+        InsiderSentiment {
+            symbol: "TSLA".to_string(),
+            year,
+            month,
+            change: mspr * 100.0,
+            mspr,
+        }
+    }
+
+    #[test]
+    fn months_are_sorted_oldest_first() {
+        let range = SymbolRange::parse("TSLA 2022-01-01 2022-12-31", 12, None).unwrap();
+        let chart = SentimentChart::new(
+            range,
+            vec![
+                month(2022, 3, -10.0),
+                month(2021, 12, 5.0),
+                month(2022, 1, 20.0),
+            ],
+        );
+        assert_eq!(chart.symbol, "TSLA");
+        let labels = chart
+            .months
+            .iter()
+            .map(SentimentChart::label)
+            .collect::<Vec<_>>();
+        assert_eq!(labels, ["2021-12", "2022-01", "2022-03"]);
+    }
+
+    fn table() -> InsiderTable {
+        let fixtures = FixtureProvider::new(Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures"));
+        let range = SymbolRange::parse("TSLA 2023-01-01 2023-12-31", 12, None).unwrap();
+        let transactions = fixtures
+            .insider_transactions(&range.symbol, range.from, range.to)
+            .unwrap();
+        InsiderTable::new(range, transactions.data)
+    }
+
+    fn codes(table: &InsiderTable) -> Vec<&str> {
+        table
+            .visible()
+            .iter()
+            .map(|transaction| transaction.transaction_code.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn transactions_are_newest_first() {
+        let table = table();
+        let dates = table
+            .transactions
+            .iter()
+            .map(|transaction| transaction.transaction_date.as_str())
+            .collect::<Vec<_>>();
+        let mut sorted = dates.clone();
+        sorted.sort_by(|a, b| b.cmp(a));
+        assert_eq!(dates, sorted);
+    }
+
+    #[test]
+    fn buys_are_purchases_and_sells_are_sales() {
+        let mut table = table();
+        table.key('b');
+        // Awards (A) and option exercises (M) add shares but aren't buys
+        assert_eq!(codes(&table), ["P"; 3]);
+        table.key('s');
+        assert_eq!(codes(&table), ["S"; 11]);
+        assert_eq!(table.filter.to_string(), "sells only");
+        table.key('a');
+        assert_eq!(table.visible().len(), 40);
+    }
+
+    #[test]
+    fn c_cycles_through_the_codes_in_the_data() {
+        let mut table = table();
+        assert_eq!(table.codes(), ["A", "F", "G", "M", "P", "S"]);
+        table.key('c');
+        assert_eq!(table.filter.code.as_deref(), Some("A"));
+        assert_eq!(codes(&table), ["A"; 9]);
+        assert_eq!(table.filter.to_string(), "all, code A");
+        for _ in 0..5 {
+            table.key('c');
+        }
+        assert_eq!(table.filter.code.as_deref(), Some("S"));
+        table.key('c');
+        assert_eq!(table.filter.code, None);
+        table.key('c');
+        table.key('a');
+        assert_eq!(table.filter.code, None);
+    }
+
+    #[test]
+    fn i_shows_only_the_selected_insider() {
+        let mut table = table();
+        table.down();
+        table.key('i');
+        let name = table.filter.insider.clone().unwrap();
+        assert!(table
+            .visible()
+            .iter()
+            .all(|transaction| transaction.name == name));
+        assert_eq!(table.selected, 0);
+        table.key('i');
+        assert_eq!(table.filter.insider, None);
+    }
+
+    #[test]
+    fn totals_average_only_priced_shares() {
+        let mut table = table();
+        table.key('b');
+        table.key('i');
+        let visible = table.visible();
+        let totals = InsiderTable::totals(&visible);
+        assert_eq!(totals.len(), 1);
+        let (name, total) = &totals[0];
+        assert_eq!(*name, "Musk Elon");
+        assert_eq!(total.change, 6093.0);
+        assert_eq!(total.count, 2);
+        let average = total.value / total.priced_shares;
+        assert!((average - (1575.0 * 223.94 + 4518.0 * 202.31) / 6093.0).abs() < 1e-9);
+    }
+}
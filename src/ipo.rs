@@ -0,0 +1,333 @@
+//! The IPO calendar for the Results window: a table of recent and upcoming IPOs that
+//! can be sorted by any column and narrowed down to one status.
+
+use std::cmp::Ordering;
+
+use chrono::NaiveDate;
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Row, Table, TableState},
+    Frame,
+};
+
+use crate::{api::Ipo, app::DateRange};
+
+/// The statuses f cycles through after showing all of them
+pub const STATUSES: [&str; 4] = ["expected", "priced", "withdrawn", "filed"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IpoColumn {
+    Date,
+    Name,
+    Symbol,
+    Exchange,
+    Price,
+    Shares,
+    Value,
+    Status,
+}
+
+impl IpoColumn {
+    const ALL: [IpoColumn; 8] = [
+        IpoColumn::Date,
+        IpoColumn::Name,
+        IpoColumn::Symbol,
+        IpoColumn::Exchange,
+        IpoColumn::Price,
+        IpoColumn::Shares,
+        IpoColumn::Value,
+        IpoColumn::Status,
+    ];
+
+    pub fn next(self) -> Self {
+        let index = Self::ALL
+            .iter()
+            .position(|column| *column == self)
+            .unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    /// Missing numbers sort below every real one
+    fn compare(self, a: &Ipo, b: &Ipo) -> Ordering {
+        let number = |a: Option<f64>, b: Option<f64>| match (a, b) {
+            (Some(a), Some(b)) => a.total_cmp(&b),
+            (a, b) => a.is_some().cmp(&b.is_some()),
+        };
+        match self {
+            IpoColumn::Date => a.date.cmp(&b.date),
+            IpoColumn::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+            IpoColumn::Symbol => a.symbol.cmp(&b.symbol),
+            IpoColumn::Exchange => a.exchange.cmp(&b.exchange),
+            IpoColumn::Price => number(low_price(a), low_price(b)),
+            IpoColumn::Shares => number(a.number_of_shares, b.number_of_shares),
+            IpoColumn::Value => number(a.total_shares_value, b.total_shares_value),
+            IpoColumn::Status => a.status.cmp(&b.status),
+        }
+    }
+}
+
+impl std::fmt::Display for IpoColumn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let output = match self {
+            IpoColumn::Date => "Date",
+            IpoColumn::Name => "Name",
+            IpoColumn::Symbol => "Symbol",
+            IpoColumn::Exchange => "Exchange",
+            IpoColumn::Price => "Price",
+            IpoColumn::Shares => "Shares",
+            IpoColumn::Value => "Value",
+            IpoColumn::Status => "Status",
+        };
+        write!(f, "{}", output)
+    }
+}
+
+/// The bottom of a range like "16.00-18.00", or the price once there is one
+fn low_price(ipo: &Ipo) -> Option<f64> {
+    let price = ipo.price.as_deref()?;
+    price.split('-').next()?.trim().parse().ok()
+}
+
+/// Finnhub sends null for what an IPO doesn't have yet
+fn text(value: &Option<String>) -> String {
+    value.clone().unwrap_or_else(|| "-".to_string())
+}
+
+/// 1.25B, 12.5M, 800.0K
+fn large(value: Option<f64>) -> String {
+    match value {
+        Some(value) if value >= 1e9 => format!("{:.2}B", value / 1e9),
+        Some(value) if value >= 1e6 => format!("{:.1}M", value / 1e6),
+        Some(value) => format!("{:.1}K", value / 1e3),
+        None => "-".to_string(),
+    }
+}
+
+/// Kept in State so the sort, filter and selection survive redraws
+#[derive(Debug, Clone)]
+pub struct IpoTable {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub ipos: Vec<Ipo>,
+    pub sort: IpoColumn,
+    pub descending: bool,
+    // Index into STATUSES, every status when None
+    pub status: Option<usize>,
+    // Index into the sorted and filtered IPOs
+    pub selected: usize,
+}
+
+impl IpoTable {
+    pub fn new(range: DateRange, ipos: Vec<Ipo>) -> Self {
+        Self {
+            from: range.from,
+            to: range.to,
+            ipos,
+            sort: IpoColumn::Date,
+            descending: false,
+            status: None,
+            selected: 0,
+        }
+    }
+
+    pub fn status_name(&self) -> &str {
+        match self.status {
+            Some(index) => STATUSES[index],
+            None => "all statuses",
+        }
+    }
+
+    pub fn visible(&self) -> Vec<&Ipo> {
+        let mut visible = self
+            .ipos
+            .iter()
+            .filter(|ipo| {
+                self.status
+                    .is_none_or(|index| ipo.status.eq_ignore_ascii_case(STATUSES[index]))
+            })
+            .collect::<Vec<_>>();
+        visible.sort_by(|a, b| {
+            let ordering = self.sort.compare(a, b);
+            if self.descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+        visible
+    }
+
+    /// s sorts by the next column, r reverses the order and f picks the next status to show
+    pub fn key(&mut self, c: char) {
+        match c {
+            's' => self.sort = self.sort.next(),
+            'r' => self.descending = !self.descending,
+            'f' => {
+                self.status = match self.status {
+                    None => Some(0),
+                    Some(index) if index + 1 < STATUSES.len() => Some(index + 1),
+                    Some(_) => None,
+                }
+            }
+            _ => return,
+        }
+        self.selected = 0;
+    }
+
+    pub fn up(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn down(&mut self) {
+        if self.selected + 1 < self.visible().len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn render<B: Backend>(&self, f: &mut Frame<B>, area: Rect, block: Block) {
+        let visible = self.visible();
+        let rows = visible.iter().map(|ipo| {
+            let colour = match ipo.status.as_str() {
+                "priced" => Color::Green,
+                "withdrawn" => Color::Red,
+                "filed" => Color::Gray,
+                _ => Color::White,
+            };
+            Row::new(vec![
+                ipo.date.clone(),
+                ipo.name.clone(),
+                text(&ipo.symbol),
+                text(&ipo.exchange),
+                text(&ipo.price),
+                large(ipo.number_of_shares),
+                large(ipo.total_shares_value),
+                ipo.status.clone(),
+            ])
+            .style(Style::default().fg(colour))
+        });
+        // The sorted column gets an arrow for which way it's sorted
+        let header = IpoColumn::ALL.iter().map(|column| {
+            if *column == self.sort {
+                format!("{column} {}", if self.descending { "▼" } else { "▲" })
+            } else {
+                column.to_string()
+            }
+        });
+        let table = Table::new(rows)
+            .header(Row::new(header).style(Style::default().add_modifier(Modifier::BOLD)))
+            .block(block)
+            .style(Style::default().fg(Color::White).bg(Color::Black))
+            .widths(&[
+                Constraint::Length(10),
+                Constraint::Min(16),
+                Constraint::Length(6),
+                Constraint::Length(8),
+                Constraint::Length(11),
+                Constraint::Length(7),
+                Constraint::Length(7),
+                Constraint::Length(9),
+            ])
+            .column_spacing(1)
+            .highlight_style(Style::default().bg(Color::DarkGray))
+            .highlight_symbol(">>");
+        let mut table_state = TableState::default();
+        if !visible.is_empty() {
+            table_state.select(Some(self.selected));
+        }
+        f.render_stateful_widget(table, area, &mut table_state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::provider::{FixtureProvider, MarketDataProvider};
+
+    fn table() -> IpoTable {
+        let from = NaiveDate::from_ymd_opt(2026, 9, 1).unwrap();
+        let to = NaiveDate::from_ymd_opt(2026, 11, 30).unwrap();
+        let fixtures = FixtureProvider::new(Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures"));
+        let calendar = fixtures.ipo_calendar(from, to).unwrap();
+        IpoTable::new(DateRange { from, to }, calendar.ipo_calendar)
+    }
+
+    fn names(table: &IpoTable) -> Vec<&str> {
+        table
+            .visible()
+            .iter()
+            .map(|ipo| ipo.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn starts_sorted_by_date() {
+        let table = table();
+        let dates = table
+            .visible()
+            .iter()
+            .map(|ipo| ipo.date.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(dates.len(), 8);
+        assert_eq!(dates.first().unwrap(), "2026-09-30");
+        assert_eq!(dates.last().unwrap(), "2026-11-05");
+        assert!(dates.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+
+    #[test]
+    fn price_sorts_by_the_bottom_of_the_range_with_missing_prices_first() {
+        let mut table = table();
+        while table.sort != IpoColumn::Price {
+            table.key('s');
+        }
+        let visible = names(&table);
+        assert_eq!(visible[0], "Cobalt Ridge Mining");
+        assert_eq!(visible[1], "Verdant Foods Co");
+        assert_eq!(visible[7], "Atlas Aerospace Inc.");
+        table.key('r');
+        assert_eq!(names(&table)[0], "Atlas Aerospace Inc.");
+    }
+
+    #[test]
+    fn f_cycles_through_the_statuses() {
+        let mut table = table();
+        table.key('f');
+        assert_eq!(table.status_name(), "expected");
+        assert_eq!(table.visible().len(), 3);
+        table.key('f');
+        assert_eq!(table.status_name(), "priced");
+        assert_eq!(table.visible().len(), 3);
+        table.key('f');
+        assert_eq!(names(&table), ["Verdant Foods Co"]);
+        table.key('f');
+        assert_eq!(names(&table), ["Cobalt Ridge Mining"]);
+        table.key('f');
+        assert_eq!(table.status_name(), "all statuses");
+        assert_eq!(table.visible().len(), 8);
+    }
+
+    #[test]
+    fn selection_stays_inside_the_visible_rows_and_resets_on_keys() {
+        let mut table = table();
+        table.key('f');
+        for _ in 0..10 {
+            table.down();
+        }
+        assert_eq!(table.selected, 2);
+        table.key('r');
+        assert_eq!(table.selected, 0);
+        table.up();
+        assert_eq!(table.selected, 0);
+    }
+
+    #[test]
+    fn large_numbers_are_shortened() {
+        assert_eq!(large(Some(1_240_000_000.0)), "1.24B");
+        assert_eq!(large(Some(6_250_000.0)), "6.2M");
+        assert_eq!(large(Some(800_000.0)), "800.0K");
+        assert_eq!(large(None), "-");
+    }
+}
@@ -1,7 +1,30 @@
 use std::fmt::Debug;
 
-pub const API_KEY: &str = include_str!("..\\key.txt");
+pub mod cache;
+pub mod chart;
+pub mod comparison;
+pub mod config;
+pub mod crypto;
+pub mod earnings;
+pub mod filings;
+pub mod financials;
+pub mod forex;
+pub mod import;
+pub mod insider;
+pub mod ipo;
+pub mod limiter;
+pub mod lots;
+pub mod news;
+pub mod peers;
+pub mod portfolio;
+pub mod provider;
+pub mod recommendation;
+pub mod reported;
+pub mod watchlist;
+
 pub const FINNHUB_URL: &str = "https://finnhub.io/api/v1";
+/// How often the Quote choice asks Finnhub for a fresh price while it's selected
+pub const QUOTE_REFRESH_SECS: u64 = 10;
 pub const EXCHANGE_CODES: [&str; 72] = [
     "AS", "AT", "AX", "BA", "BC", "BD", "BE", "BK", "BO", "BR", "CA", "CN", "CO", "CR", "DB", "DE",
     "DU", "F", "HE", "HK", "HM", "IC", "IR", "IS", "JK", "JO", "KL", "KQ", "KS", "L", "LN", "LS",
@@ -14,22 +37,25 @@ pub const EXCHANGE_CODES: [&str; 72] = [
 pub enum Window {
     ApiChoice,
     Results,
+    Watchlist,
 }
 
 pub mod app {
     use std::{
+        collections::{HashMap, HashSet},
         fmt::Debug,
         fs::File,
-        io::{Stdout, Write},
+        io::Write,
+        path::Path,
         sync::mpsc::{Receiver, SyncSender},
+        time::{Duration, Instant},
     };
 
     use anyhow::{Context, Error};
-    use chrono::{Months, TimeZone, Utc};
+    use chrono::{Months, NaiveDate, Utc};
     use crossterm::event::{read, Event, KeyCode, KeyEvent};
-    use serde::de::DeserializeOwned;
     use tui::{
-        backend::CrosstermBackend,
+        backend::Backend,
         layout::{Alignment, Constraint, Direction, Layout},
         style::{Color, Modifier, Style},
         text::Span,
@@ -38,8 +64,31 @@ pub mod app {
     };
 
     use crate::{
-        api::{CompanyNews, CompanyProfile, MarketNews, StockSymbol},
-        Window, API_KEY, EXCHANGE_CODES, FINNHUB_URL,
+        api::{
+            BasicFinancials, Candles, CompanyNews, CryptoSymbol, EarningsRelease, EarningsSurprise,
+            ForexRates, ForexSymbol, InsiderSentiment, InsiderTransaction, Ipo, MarketNews, Quote,
+            RecommendationTrend, ReportedFiling, Resolution, SecFiling, StockSymbol,
+        },
+        chart::CandleChart,
+        comparison::{ComparisonTable, PeerMetrics, PeerSet},
+        crypto,
+        earnings::{EarningsTable, EpsHistory},
+        filings::FilingsList,
+        financials::{FinancialsView, Period},
+        forex::{symbols_text, ForexRequest},
+        import::{builtin_profiles, import_csv, ImportProfile},
+        insider::{InsiderTable, SentimentChart},
+        ipo::IpoTable,
+        limiter::ApiStatus,
+        lots::Transaction,
+        news::{MarketNewsFeeds, NewsCategory, NewsItem, NewsList},
+        peers::PeerList,
+        portfolio::Portfolio,
+        provider::MarketDataProvider,
+        recommendation::RecommendationChart,
+        reported::{ReportedRequest, ReportedView},
+        watchlist::{Watchlist, WATCHLIST_REFRESH_SECS},
+        Window, QUOTE_REFRESH_SECS,
     };
 
     pub fn handle_event(sender: &SyncSender<Command>) {
@@ -68,6 +117,18 @@ pub mod app {
                     (KeyCode::Right, _) => {
                         sender.send(Command::Right).unwrap();
                     }
+                    (KeyCode::Up, _) => {
+                        sender.send(Command::Up).unwrap();
+                    }
+                    (KeyCode::Down, _) => {
+                        sender.send(Command::Down).unwrap();
+                    }
+                    (KeyCode::PageUp, _) => {
+                        sender.send(Command::PageUp).unwrap();
+                    }
+                    (KeyCode::PageDown, _) => {
+                        sender.send(Command::PageDown).unwrap();
+                    }
                     (KeyCode::Tab, _) => {
                         sender.send(Command::Tab).unwrap();
                     }
@@ -75,7 +136,7 @@ pub mod app {
                 }
             }
             Event::Mouse(_) => {}
-            Event::Resize(num1, num2) => {
+            Event::Resize(_num1, _num2) => {
                 //println!("Window has been resized to {num1}, {num2}");
             }
             Event::Paste(_s) => {}
@@ -99,6 +160,25 @@ pub mod app {
                     ApiChoice::MarketNews,
                     ApiChoice::CompanyNews,
                     ApiChoice::GetMarket,
+                    ApiChoice::Quote,
+                    ApiChoice::Candles,
+                    ApiChoice::Portfolio,
+                    ApiChoice::Import,
+                    ApiChoice::BasicFinancials,
+                    ApiChoice::InsiderSentiment,
+                    ApiChoice::InsiderTransactions,
+                    ApiChoice::FinancialsReported,
+                    ApiChoice::SecFilings,
+                    ApiChoice::Recommendations,
+                    ApiChoice::Earnings,
+                    ApiChoice::EpsSurprises,
+                    ApiChoice::Ipos,
+                    ApiChoice::Peers,
+                    ApiChoice::ComparePeers,
+                    ApiChoice::ForexSymbols,
+                    ApiChoice::ForexRates,
+                    ApiChoice::CryptoSymbols,
+                    ApiChoice::CryptoCandles,
                 ],
                 current_index: 0,
             }
@@ -120,6 +200,17 @@ pub mod app {
                 next_number
             };
         }
+        // The choices are drawn 3 to a row, so up and down move by a row
+        pub fn up(&mut self) {
+            if let Some(okay_number) = self.current_index.checked_sub(3) {
+                self.current_index = okay_number;
+            }
+        }
+        pub fn down(&mut self) {
+            if self.current_index + 3 < self.all_apis.len() {
+                self.current_index += 3;
+            }
+        }
         pub fn current_api(&self) -> ApiChoice {
             self.all_apis[self.current_index]
         }
@@ -129,6 +220,7 @@ pub mod app {
     pub struct FinanceClient {
         pub sender: SyncSender<Command>,
         pub receiver: Receiver<ApiCommand>,
+        pub provider: Box<dyn MarketDataProvider>,
     }
 
     #[derive(Debug)]
@@ -137,26 +229,230 @@ pub mod app {
         pub api_choices: TotalApiChoices,
         pub current_market: String,
         pub companies: Vec<String>,
+        // What each symbol in the market's list is quoted in, for portfolio values in another currency
+        pub currencies: HashMap<String, String>,
+        // The crypto exchange whose symbols were loaded last, searched as Crypto Symbols is typed in
+        pub crypto_exchange: Option<String>,
+        pub crypto_symbols: Vec<CryptoSymbol>,
         pub current_content: String,
         pub search_string: String,
         pub api_sender: SyncSender<ApiCommand>,
         pub receiver: Receiver<Command>,
-        pub waiting_for_result: bool
+        pub waiting_for_result: bool,
+        // Symbol the Quote choice keeps refreshing, and when it last asked for it
+        pub quote_symbol: Option<String>,
+        // The last symbol anything was asked for, used when the search box only has dates
+        pub current_symbol: Option<String>,
+        pub last_quote_request: Option<Instant>,
+        pub result_view: ResultView,
+        // How old the reply in the Results window is if it came out of the cache
+        pub cache_age: Option<Duration>,
+        // Set while a request is held up by the rate limiter or being retried
+        pub api_status: Option<ApiStatus>,
+        pub watchlist: Watchlist,
+        pub last_watchlist_request: Option<Instant>,
+        pub portfolio: Portfolio,
+        pub news: MarketNewsFeeds,
+        // Built in profiles plus any from config.json
+        pub import_profiles: HashMap<String, ImportProfile>,
+    }
+
+    /// What the Results window is showing. Text is whatever is in current_content.
+    #[derive(Debug)]
+    pub enum ResultView {
+        Text,
+        Chart(CandleChart),
+        // The holdings themselves live in State::portfolio
+        Portfolio,
+        // The feeds live in State::news, this is whichever category it's on
+        MarketNews,
+        // Realized gains from the portfolio's sells
+        Gains,
+        Financials(Box<FinancialsView>),
+        InsiderSentiment(SentimentChart),
+        InsiderTransactions(InsiderTable),
+        FinancialsReported(ReportedView),
+        SecFilings(FilingsList),
+        Recommendations(RecommendationChart),
+        Earnings(EarningsTable),
+        EpsSurprises(EpsHistory),
+        Ipos(IpoTable),
+        Peers(PeerList),
+        Comparison(ComparisonTable),
+        CompanyNews(String, NewsList),
+    }
+
+    /// A date typed into the search box
+    fn parse_date(date: &str) -> Result<NaiveDate, Error> {
+        NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .with_context(|| format!("Couldn't read {date} as a date, use YYYY-MM-DD"))
+    }
+
+    /// Candles for a symbol, typed into the search box as
+    /// SYMBOL [RESOLUTION] [FROM] [TO], e.g. "AAPL D 2022-01-01 2022-06-30"
+    #[derive(Debug, Clone)]
+    pub struct CandleRequest {
+        pub symbol: String,
+        pub resolution: Resolution,
+        pub from: NaiveDate,
+        pub to: NaiveDate,
+    }
+
+    impl CandleRequest {
+        pub fn parse(input: &str) -> Result<Self, Error> {
+            let words = input.split_whitespace().collect::<Vec<_>>();
+            let symbol = match words.first() {
+                Some(symbol) => symbol.to_uppercase(),
+                None => {
+                    return Err(anyhow::anyhow!(
+                        "Type a symbol first, e.g. AAPL D 2022-01-01 2022-06-30"
+                    ))
+                }
+            };
+            let resolution = match words.get(1) {
+                Some(resolution) => resolution.parse()?,
+                None => Resolution::Day,
+            };
+            let to = match words.get(3) {
+                Some(to) => parse_date(to)?,
+                None => Utc::today().naive_utc(),
+            };
+            let from = match words.get(2) {
+                Some(from) => parse_date(from)?,
+                // Intraday candles only go back so far, so ask for less of them by default
+                None => match resolution {
+                    Resolution::Day => to - Months::new(12),
+                    Resolution::Week | Resolution::Month => to - Months::new(60),
+                    _ => to - chrono::Duration::days(7),
+                },
+            };
+            if from > to {
+                return Err(anyhow::anyhow!(format!("{from} is after {to}")));
+            }
+            Ok(Self {
+                symbol,
+                resolution,
+                from,
+                to,
+            })
+        }
+    }
+
+    /// A symbol and a date range, typed into the search box as SYMBOL [FROM] [TO],
+    /// e.g. "TSLA 2022-01-01 2022-12-31". The symbol can be left out to use the last
+    /// one looked at, and without dates it's the last `default_months` months.
+    #[derive(Debug, Clone)]
+    pub struct SymbolRange {
+        pub symbol: String,
+        pub from: NaiveDate,
+        pub to: NaiveDate,
+    }
+
+    impl SymbolRange {
+        pub fn parse(
+            input: &str,
+            default_months: u32,
+            current: Option<&str>,
+        ) -> Result<Self, Error> {
+            let mut words = input.split_whitespace().collect::<Vec<_>>();
+            let symbol = match (words.first(), current) {
+                (Some(word), _) if parse_date(word).is_err() => words.remove(0).to_uppercase(),
+                (_, Some(current)) => current.to_string(),
+                _ => {
+                    return Err(anyhow::anyhow!(
+                        "Type a symbol first, e.g. TSLA 2022-01-01 2022-12-31"
+                    ))
+                }
+            };
+            let to = match words.get(1) {
+                Some(to) => parse_date(to)?,
+                None => Utc::today().naive_utc(),
+            };
+            let from = match words.first() {
+                Some(from) => parse_date(from)?,
+                None => to - Months::new(default_months),
+            };
+            if from > to {
+                return Err(anyhow::anyhow!(format!("{from} is after {to}")));
+            }
+            Ok(Self { symbol, from, to })
+        }
+    }
+
+    /// Dates for the calendars, typed into the search box as [FROM] [TO]. FROM defaults to
+    /// days_back before today and TO to days_ahead after today or FROM, whichever is later.
+    #[derive(Debug, Clone, Copy)]
+    pub struct DateRange {
+        pub from: NaiveDate,
+        pub to: NaiveDate,
+    }
+
+    impl DateRange {
+        pub fn parse(input: &str, days_back: i64, days_ahead: i64) -> Result<Self, Error> {
+            let words = input.split_whitespace().collect::<Vec<_>>();
+            let today = Utc::today().naive_utc();
+            let from = match words.first() {
+                Some(from) => parse_date(from)?,
+                None => today - chrono::Duration::days(days_back),
+            };
+            let to = match words.get(1) {
+                Some(to) => parse_date(to)?,
+                None => from.max(today) + chrono::Duration::days(days_ahead),
+            };
+            if from > to {
+                return Err(anyhow::anyhow!(format!("{from} is after {to}")));
+            }
+            Ok(Self { from, to })
+        }
     }
 
     pub enum Command {
         Backspace,
         Char(char),
         CompanyInfo(Vec<StockSymbol>),
-        //CompanyNews,
+        CompanyNews(String, NewsList),
         Enter,
         Esc,
         Left,
         // Gets something that needs to go in the result window
         ResultWindow(String),
+        // Candles to draw in the result window instead of text
+        Chart(CandleChart),
+        Financials(Box<FinancialsView>),
+        InsiderSentiment(SentimentChart),
+        InsiderTransactions(InsiderTable),
+        FinancialsReported(ReportedView),
+        SecFilings(FilingsList),
+        Recommendations(RecommendationChart),
+        Earnings(EarningsTable),
+        EpsSurprises(EpsHistory),
+        Ipos(IpoTable),
+        Peers(PeerList),
+        Comparison(ComparisonTable),
+        CryptoSymbols(String, Vec<CryptoSymbol>),
+        MarketNews(NewsCategory, Vec<MarketNews>),
+        // Sent after a result if it came out of the cache
+        CacheAge(Duration),
+        // A request is waiting on the rate limiter or being retried
+        ApiStatus(ApiStatus),
         StockSymbols(Result<Vec<StockSymbol>, Error>),
         Right,
+        Up,
+        Down,
+        PageUp,
+        PageDown,
         Tab,
+        // Quotes for the symbols on the watchlist
+        WatchlistQuotes(Vec<(String, Result<Quote, String>)>),
+        // Quotes to price the portfolio with, rates against its base currency if it has one
+        // and the currency of each symbol that wasn't in the market's symbol list
+        PortfolioQuotes(
+            Vec<(String, Result<Quote, String>)>,
+            Option<Result<ForexRates, String>>,
+            Vec<(String, String)>,
+        ),
+        // Sent every second from its own thread so State can refresh things on a timer
+        Tick,
     }
 
     pub enum ApiCommand {
@@ -165,7 +461,50 @@ pub mod app {
         CompanyProfile(String),
         // current_market as a String
         StockSymbols(String),
-        MarketNews,
+        // category and the newest id already fetched for it
+        MarketNews(NewsCategory, i64),
+        // symbol to get a quote for
+        Quote(String),
+        Candles(CandleRequest),
+        // Every symbol on the watchlist
+        WatchlistQuotes(Vec<String>),
+        // Every symbol in the portfolio, its base currency and the symbols to look up the
+        // currency of. One command so State never waits on the queue for a second one.
+        PortfolioQuotes(Vec<String>, Option<String>, Vec<String>),
+        // symbol to get metrics and series for
+        BasicFinancials(String),
+        InsiderSentiment(SymbolRange),
+        InsiderTransactions(SymbolRange),
+        FinancialsReported(ReportedRequest),
+        SecFilings(SymbolRange),
+        // symbol to get analyst ratings for
+        Recommendations(String),
+        Earnings(DateRange),
+        // symbol and how many quarters to show
+        EpsSurprises(String, Option<u32>),
+        Ipos(DateRange),
+        // symbol to find companies in the same industry as
+        Peers(String),
+        ComparePeers(PeerSet),
+        ForexExchanges,
+        // exchange and what to narrow its pairs down to
+        ForexSymbols(String, Option<String>),
+        ForexRates(ForexRequest),
+        CryptoExchanges,
+        // exchange to list the symbols of
+        CryptoSymbols(String),
+        CryptoCandles(CandleRequest),
+    }
+
+    /// 42s, 5m, 3h, 2d
+    fn format_age(age: Duration) -> String {
+        let secs = age.as_secs();
+        match secs {
+            0..=59 => format!("{secs}s"),
+            60..=3599 => format!("{}m", secs / 60),
+            3600..=86399 => format!("{}h", secs / 3600),
+            _ => format!("{}d", secs / 86400),
+        }
     }
 
     fn make_table(all_choices: Vec<Span>) -> Table {
@@ -203,13 +542,31 @@ pub mod app {
                 Command::Backspace => {
                     self.search_string.pop();
                 }
-                Command::Char(c) => {
-                    self.search_string.push(c);
+                // Typing goes to the search box, but the Results window uses letters as keys
+                Command::Char(c) => match self.current_window {
+                    Window::ApiChoice => self.search_string.push(c),
+                    Window::Results => self.result_key(c),
+                    Window::Watchlist => self.watchlist_key(c),
+                },
+                // Enter on a peer opens its profile instead of asking for peers again
+                Command::Enter
+                    if self.current_window == Window::Results
+                        && matches!(self.result_view, ResultView::Peers(_)) =>
+                {
+                    self.open_peer();
                 }
                 Command::Enter => match self.api_choice() {
-                    ApiChoice::CompanyProfile => {
-                        self.send_command(ApiCommand::CompanyProfile(self.search_string.clone()));
-                    }
+                    ApiChoice::CompanyProfile => match self.search_string.trim() {
+                        "" => {
+                            self.result_view = ResultView::Text;
+                            self.current_content =
+                                "Type a symbol for its profile, e.g. AAPL".to_string();
+                        }
+                        symbol => {
+                            self.current_symbol = Some(symbol.to_uppercase());
+                            self.send_command(ApiCommand::CompanyProfile(symbol.to_string()));
+                        }
+                    },
                     ApiChoice::GetMarket => {
                         if self.current_market == self.search_string  {
                             self.current_content = format!("Already using market {}", self.current_market);
@@ -218,11 +575,248 @@ pub mod app {
                         }
                     }
                     ApiChoice::MarketNews => {
-                        self.send_command(ApiCommand::MarketNews);
+                        // Without a category it's whichever was looked at last
+                        let category = match self.search_string.trim() {
+                            "" => self.news.category,
+                            typed => match typed.parse() {
+                                Ok(category) => category,
+                                Err(e) => {
+                                    self.result_view = ResultView::Text;
+                                    self.current_content = format!("{e}");
+                                    return;
+                                }
+                            },
+                        };
+                        self.news.open(category);
+                        self.result_view = ResultView::MarketNews;
+                        self.refresh_news();
                     }
                     ApiChoice::CompanyNews => {
-                        self.send_command(ApiCommand::CompanyNews(self.search_string.clone()));
+                        if let Some(symbol) = self.symbol_or_current() {
+                            self.send_command(ApiCommand::CompanyNews(symbol));
+                        }
+                    }
+                    ApiChoice::Quote => {
+                        let symbol = self.search_string.trim().to_uppercase();
+                        if symbol.is_empty() {
+                            self.result_view = ResultView::Text;
+                            self.current_content =
+                                "Type a symbol for its quote, e.g. AAPL".to_string();
+                            return;
+                        }
+                        self.quote_symbol = Some(symbol.clone());
+                        self.current_symbol = Some(symbol.clone());
+                        self.last_quote_request = Some(Instant::now());
+                        self.send_command(ApiCommand::Quote(symbol));
+                    }
+                    ApiChoice::Candles => match CandleRequest::parse(&self.search_string) {
+                        Ok(request) => {
+                            self.current_symbol = Some(request.symbol.clone());
+                            self.send_command(ApiCommand::Candles(request));
+                        }
+                        Err(e) => {
+                            self.result_view = ResultView::Text;
+                            self.current_content = e.to_string();
+                        }
+                    },
+                    ApiChoice::Portfolio => {
+                        // Enter on its own just prices what's already there
+                        if let Some(currency) = Portfolio::base_command(&self.search_string) {
+                            if let Err(e) = self.portfolio.set_base_currency(currency) {
+                                self.result_view = ResultView::Text;
+                                self.current_content = e.to_string();
+                                return;
+                            }
+                            self.search_string.clear();
+                        } else if !self.search_string.trim().is_empty() {
+                            let res =
+                                Transaction::parse(&self.search_string, self.portfolio.next_id())
+                                    .and_then(|transaction| self.portfolio.add(transaction));
+                            if let Err(e) = res {
+                                self.result_view = ResultView::Text;
+                                self.current_content = e.to_string();
+                                return;
+                            }
+                            self.search_string.clear();
+                        }
+                        self.result_view = ResultView::Portfolio;
+                        self.refresh_portfolio();
+                    }
+                    ApiChoice::BasicFinancials => {
+                        let symbol = self.search_string.trim().to_uppercase();
+                        if symbol.is_empty() {
+                            self.result_view = ResultView::Text;
+                            self.current_content =
+                                "Type a symbol for its basic financials, e.g. AAPL".to_string();
+                            return;
+                        }
+                        self.current_symbol = Some(symbol.clone());
+                        self.send_command(ApiCommand::BasicFinancials(symbol));
+                    }
+                    ApiChoice::InsiderSentiment
+                    | ApiChoice::InsiderTransactions
+                    | ApiChoice::SecFilings => {
+                        match SymbolRange::parse(
+                            &self.search_string,
+                            12,
+                            self.current_symbol.as_deref(),
+                        ) {
+                            Ok(range) => {
+                                self.current_symbol = Some(range.symbol.clone());
+                                let command = match self.api_choice() {
+                                    ApiChoice::InsiderSentiment => {
+                                        ApiCommand::InsiderSentiment(range)
+                                    }
+                                    ApiChoice::SecFilings => ApiCommand::SecFilings(range),
+                                    _ => ApiCommand::InsiderTransactions(range),
+                                };
+                                self.send_command(command);
+                            }
+                            Err(e) => {
+                                self.result_view = ResultView::Text;
+                                self.current_content = e.to_string();
+                            }
+                        }
+                    }
+                    ApiChoice::Recommendations => {
+                        if let Some(symbol) = self.symbol_or_current() {
+                            self.send_command(ApiCommand::Recommendations(symbol));
+                        }
+                    }
+                    // A week back so the latest surprises are there too
+                    ApiChoice::Earnings => match DateRange::parse(&self.search_string, 7, 14) {
+                        Ok(range) => self.send_command(ApiCommand::Earnings(range)),
+                        Err(e) => {
+                            self.result_view = ResultView::Text;
+                            self.current_content = e.to_string();
+                        }
+                    },
+                    ApiChoice::EpsSurprises => {
+                        let words = self.search_string.split_whitespace().collect::<Vec<_>>();
+                        // SYMBOL [QUARTERS], or just QUARTERS for the last symbol looked at
+                        let (symbol, quarters) = match words.as_slice() {
+                            [quarters] if quarters.parse::<u32>().is_ok() => {
+                                (self.current_symbol.clone(), Some(*quarters))
+                            }
+                            [symbol, rest @ ..] => {
+                                (Some(symbol.to_uppercase()), rest.first().copied())
+                            }
+                            [] => (self.current_symbol.clone(), None),
+                        };
+                        let quarters = quarters
+                            .map(|quarters| {
+                                quarters.parse::<u32>().with_context(|| {
+                                    format!("Couldn't read {quarters} as a number of quarters")
+                                })
+                            })
+                            .transpose();
+                        match (symbol, quarters) {
+                            (Some(symbol), Ok(quarters)) => {
+                                self.current_symbol = Some(symbol.clone());
+                                self.send_command(ApiCommand::EpsSurprises(symbol, quarters));
+                            }
+                            (None, _) => {
+                                self.result_view = ResultView::Text;
+                                self.current_content = "Type a symbol first, e.g. AAPL 8".into();
+                            }
+                            (_, Err(e)) => {
+                                self.result_view = ResultView::Text;
+                                self.current_content = e.to_string();
+                            }
+                        }
+                    }
+                    ApiChoice::Peers => {
+                        if let Some(symbol) = self.symbol_or_current() {
+                            self.send_command(ApiCommand::Peers(symbol));
+                        }
+                    }
+                    ApiChoice::CryptoSymbols => {
+                        let command = match self.search_string.trim() {
+                            "" => ApiCommand::CryptoExchanges,
+                            exchange => ApiCommand::CryptoSymbols(exchange.to_lowercase()),
+                        };
+                        self.search_string.clear();
+                        self.send_command(command);
                     }
+                    ApiChoice::CryptoCandles if self.search_string.trim().is_empty() => {
+                        self.result_view = ResultView::Text;
+                        self.current_content =
+                            "Type a symbol first, e.g. BINANCE:BTCUSDT D 2025-01-01 2025-06-30"
+                                .into();
+                    }
+                    ApiChoice::CryptoCandles => match CandleRequest::parse(&self.search_string) {
+                        Ok(mut request) => {
+                            request.symbol = crypto::resolve(&self.crypto_symbols, &request.symbol);
+                            self.send_command(ApiCommand::CryptoCandles(request));
+                        }
+                        Err(e) => {
+                            self.result_view = ResultView::Text;
+                            self.current_content = e.to_string();
+                        }
+                    },
+                    ApiChoice::ForexSymbols => {
+                        let mut words = self.search_string.split_whitespace();
+                        let command = match words.next() {
+                            Some(exchange) => ApiCommand::ForexSymbols(
+                                exchange.to_lowercase(),
+                                words.next().map(str::to_string),
+                            ),
+                            None => ApiCommand::ForexExchanges,
+                        };
+                        self.send_command(command);
+                    }
+                    ApiChoice::ForexRates => match ForexRequest::parse(&self.search_string) {
+                        Ok(request) => self.send_command(ApiCommand::ForexRates(request)),
+                        Err(e) => {
+                            self.result_view = ResultView::Text;
+                            self.current_content = e.to_string();
+                        }
+                    },
+                    ApiChoice::ComparePeers => {
+                        match PeerSet::parse(&self.search_string, self.current_symbol.as_deref()) {
+                            Ok(set) => {
+                                self.current_symbol = Some(set.main_symbol().to_string());
+                                self.send_command(ApiCommand::ComparePeers(set));
+                            }
+                            Err(e) => {
+                                self.result_view = ResultView::Text;
+                                self.current_content = e.to_string();
+                            }
+                        }
+                    }
+                    ApiChoice::Ipos => match DateRange::parse(&self.search_string, 30, 30) {
+                        Ok(range) => self.send_command(ApiCommand::Ipos(range)),
+                        Err(e) => {
+                            self.result_view = ResultView::Text;
+                            self.current_content = e.to_string();
+                        }
+                    },
+                    ApiChoice::FinancialsReported => {
+                        match ReportedRequest::parse(
+                            &self.search_string,
+                            self.current_symbol.as_deref(),
+                        ) {
+                            Ok(request) => {
+                                self.current_symbol = Some(request.symbol.clone());
+                                self.send_command(ApiCommand::FinancialsReported(request));
+                            }
+                            Err(e) => {
+                                self.result_view = ResultView::Text;
+                                self.current_content = e.to_string();
+                            }
+                        }
+                    }
+                    ApiChoice::Import => match self.import() {
+                        Ok(message) => {
+                            self.search_string.clear();
+                            self.result_view = ResultView::Text;
+                            self.current_content = message;
+                        }
+                        Err(e) => {
+                            self.result_view = ResultView::Text;
+                            self.current_content = e.to_string();
+                        }
+                    },
                     _ => {}
                 },
                 Command::Esc => {
@@ -234,16 +828,106 @@ pub mod app {
                     }
                 }
                 Command::ResultWindow(s) => {
-                    self.waiting_for_result = false;
+                    self.show(ResultView::Text);
                     self.current_content = s;
                 }
+                Command::Chart(chart) => self.show(ResultView::Chart(chart)),
+                Command::Financials(financials) => self.show(ResultView::Financials(financials)),
+                Command::InsiderSentiment(sentiment) => {
+                    self.show(ResultView::InsiderSentiment(sentiment))
+                }
+                Command::InsiderTransactions(table) => {
+                    self.show(ResultView::InsiderTransactions(table))
+                }
+                Command::FinancialsReported(view) => {
+                    self.show(ResultView::FinancialsReported(view))
+                }
+                Command::Recommendations(chart) => self.show(ResultView::Recommendations(chart)),
+                Command::Earnings(mut table) => {
+                    table.set_mine(
+                        self.watchlist
+                            .symbols
+                            .iter()
+                            .cloned()
+                            .chain(self.portfolio.symbols()),
+                    );
+                    self.show(ResultView::Earnings(table));
+                }
+                Command::EpsSurprises(history) => self.show(ResultView::EpsSurprises(history)),
+                Command::Peers(mut list) => {
+                    list.describe(&self.companies);
+                    self.show(ResultView::Peers(list));
+                }
+                Command::CompanyNews(symbol, list) => {
+                    self.show(ResultView::CompanyNews(symbol, list))
+                }
+                Command::MarketNews(category, items) => {
+                    self.finish_request();
+                    self.news.merge(category, items);
+                }
+                Command::CryptoSymbols(exchange, symbols) => {
+                    self.show(ResultView::Text);
+                    self.current_content = format!(
+                        "{} symbols on {exchange}, type to search them:\n\n{}",
+                        symbols.len(),
+                        crypto::search(&symbols, "")
+                    );
+                    self.crypto_exchange = Some(exchange);
+                    self.crypto_symbols = symbols;
+                }
+                Command::Comparison(table) => self.show(ResultView::Comparison(table)),
+                Command::Ipos(table) => self.show(ResultView::Ipos(table)),
+                Command::SecFilings(list) => self.show(ResultView::SecFilings(list)),
+                Command::CacheAge(age) => {
+                    self.cache_age = Some(age);
+                }
+                Command::ApiStatus(api_status) => {
+                    self.api_status = Some(api_status);
+                }
                 Command::Right => {
                     if self.current_window == Window::ApiChoice {
                         self.api_choices.right();
                     }
                 }
+                Command::Up => match self.current_window {
+                    Window::ApiChoice => self.api_choices.up(),
+                    Window::Watchlist => self.watchlist.up(),
+                    Window::Results => self.result_up(),
+                },
+                Command::Down => match self.current_window {
+                    Window::ApiChoice => self.api_choices.down(),
+                    Window::Watchlist => self.watchlist.down(),
+                    Window::Results => self.result_down(),
+                },
+                Command::PageUp => {
+                    if self.current_window == Window::Results {
+                        self.result_page(false);
+                    }
+                }
+                Command::PageDown => {
+                    if self.current_window == Window::Results {
+                        self.result_page(true);
+                    }
+                }
+                Command::WatchlistQuotes(quotes) => {
+                    self.watchlist.update_quotes(quotes);
+                }
+                Command::PortfolioQuotes(quotes, rates, currencies) => {
+                    self.finish_request();
+                    self.portfolio.update_quotes(quotes);
+                    self.portfolio.currencies.extend(currencies);
+                    if let Some(rates) = rates {
+                        self.portfolio.rates = Some(rates);
+                    }
+                }
                 Command::StockSymbols(stock_symbols_res) => match stock_symbols_res {
                     Ok(stock_symbols) => {
+                        self.currencies = stock_symbols
+                            .iter()
+                            .map(|stock_symbol| {
+                                (stock_symbol.symbol.clone(), stock_symbol.currency.clone())
+                            })
+                            .collect();
                         let as_companies = stock_symbols
                             .into_iter()
                             .map(|stock_symbols| {
@@ -251,15 +935,20 @@ pub mod app {
                             })
                             .collect::<Vec<String>>();
                         self.companies = as_companies;
-                        self.waiting_for_result = false;
+                        self.finish_request();
                     }
                     Err(e) => {
+                        self.show(ResultView::Text);
                         self.current_content = e.to_string();
                     }
                 },
                 Command::Tab => {
                     self.switch_window();
                 }
+                Command::Tick => {
+                    self.refresh_quote();
+                    self.refresh_watchlist(false);
+                }
                 Command::CompanyInfo(company_info) => {
                     //self.companies = company_info.clone();
 
@@ -283,19 +972,357 @@ pub mod app {
             // Symbol Search should happen every time the user has selected Symbol Search
             // and search_string is at least one character long
             if self.api_choice() == ApiChoice::SymbolSearch && !self.search_string.is_empty() {
+                self.result_view = ResultView::Text;
                 self.current_content = self.company_search(&self.search_string);
                 if self.current_content.is_empty() {
                     self.current_content = "Still waiting for market info".into();
                 }
             }
+            // Same for Crypto Symbols once an exchange's symbols are there to search
+            if self.api_choice() == ApiChoice::CryptoSymbols && !self.search_string.is_empty() {
+                if let Some(exchange) = &self.crypto_exchange {
+                    self.result_view = ResultView::Text;
+                    self.current_content =
+                        crypto::search(&self.crypto_symbols, &self.search_string);
+                    if self.current_content.is_empty() {
+                        self.current_content = format!(
+                            "No {exchange} symbols match {}, Enter loads the symbols of an exchange called that",
+                            self.search_string
+                        );
+                    }
+                }
+            }
+        }
+
+        /// Letters typed while the Results window is selected
+        pub fn result_key(&mut self, c: char) {
+            let res = match (&mut self.result_view, c) {
+                (ResultView::Chart(chart), 'm') => {
+                    chart.mode.toggle();
+                    Ok(())
+                }
+                (ResultView::Financials(financials), 'p') => {
+                    financials.toggle_period();
+                    Ok(())
+                }
+                (ResultView::InsiderTransactions(table), c) => {
+                    table.key(c);
+                    Ok(())
+                }
+                (ResultView::FinancialsReported(view), c) => {
+                    view.key(c);
+                    Ok(())
+                }
+                (ResultView::SecFilings(list), c) => list.key(c),
+                (ResultView::Earnings(table), c) => {
+                    table.key(c);
+                    Ok(())
+                }
+                (ResultView::Ipos(table), c) => {
+                    table.key(c);
+                    Ok(())
+                }
+                (ResultView::Comparison(table), c) => {
+                    table.key(c);
+                    Ok(())
+                }
+                (ResultView::Peers(list), 'c') => {
+                    let mut symbols = vec![list.symbol.clone()];
+                    symbols.extend(list.peers.iter().map(|peer| peer.symbol.clone()));
+                    self.send_command(ApiCommand::ComparePeers(PeerSet::Symbols(symbols)));
+                    Ok(())
+                }
+                (ResultView::MarketNews, 'c') => {
+                    self.news.open(self.news.category.next());
+                    self.refresh_news();
+                    Ok(())
+                }
+                (ResultView::MarketNews, 'r') => {
+                    self.refresh_news();
+                    Ok(())
+                }
+                (ResultView::MarketNews, c) => {
+                    if let Some(list) = self.news.current_mut() {
+                        list.key(c);
+                    }
+                    Ok(())
+                }
+                (ResultView::CompanyNews(_, list), c) => {
+                    list.key(c);
+                    Ok(())
+                }
+                (ResultView::Portfolio, 'd') => self.portfolio.remove_selected(),
+                (ResultView::Portfolio, 'u') => self.portfolio.undo(),
+                (ResultView::Portfolio, 'm') => self.portfolio.next_method(),
+                (ResultView::Portfolio, 'g') => {
+                    self.result_view = ResultView::Gains;
+                    Ok(())
+                }
+                (ResultView::Portfolio, 'r') => {
+                    self.refresh_portfolio();
+                    Ok(())
+                }
+                (ResultView::Gains, 'p') => {
+                    self.result_view = ResultView::Portfolio;
+                    Ok(())
+                }
+                (ResultView::Gains, 'e') => self.portfolio.export_gains().map(|path| {
+                    self.result_view = ResultView::Text;
+                    self.current_content = format!("Exported realized gains to {}", path.display());
+                }),
+                _ => Ok(()),
+            };
+            if let Err(e) = res {
+                self.result_view = ResultView::Text;
+                self.current_content = e.to_string();
+            }
+        }
+
+        pub fn result_up(&mut self) {
+            match &mut self.result_view {
+                ResultView::Portfolio => self.portfolio.up(),
+                ResultView::MarketNews => {
+                    if let Some(list) = self.news.current_mut() {
+                        list.up();
+                    }
+                }
+                ResultView::CompanyNews(_, list) => list.up(),
+                ResultView::Financials(financials) => financials.up(),
+                ResultView::InsiderTransactions(table) => table.up(),
+                ResultView::FinancialsReported(view) => view.up(),
+                ResultView::SecFilings(list) => list.up(),
+                ResultView::Earnings(table) => table.up(),
+                ResultView::Ipos(table) => table.up(),
+                ResultView::Peers(list) => list.up(),
+                ResultView::Comparison(table) => table.up(),
+                _ => {}
+            }
+        }
+
+        /// Only the news lists are paged, everything else fits or scrolls a row at a time
+        pub fn result_page(&mut self, down: bool) {
+            let list = match &mut self.result_view {
+                ResultView::MarketNews => self.news.current_mut(),
+                ResultView::CompanyNews(_, list) => Some(list),
+                _ => None,
+            };
+            match (list, down) {
+                (Some(list), true) => list.page_down(),
+                (Some(list), false) => list.page_up(),
+                (None, _) => {}
+            }
+        }
+
+        pub fn result_down(&mut self) {
+            match &mut self.result_view {
+                ResultView::Portfolio => self.portfolio.down(),
+                ResultView::MarketNews => {
+                    if let Some(list) = self.news.current_mut() {
+                        list.down();
+                    }
+                }
+                ResultView::CompanyNews(_, list) => list.down(),
+                ResultView::Financials(financials) => financials.down(),
+                ResultView::InsiderTransactions(table) => table.down(),
+                ResultView::FinancialsReported(view) => view.down(),
+                ResultView::SecFilings(list) => list.down(),
+                ResultView::Earnings(table) => table.down(),
+                ResultView::Ipos(table) => table.down(),
+                ResultView::Peers(list) => list.down(),
+                ResultView::Comparison(table) => table.down(),
+                _ => {}
+            }
+        }
+
+        /// Shows the company profile of the peer selected in the Results window
+        pub fn open_peer(&mut self) {
+            let ResultView::Peers(list) = &self.result_view else {
+                return;
+            };
+            if let Some(symbol) = list.selected_symbol() {
+                let symbol = symbol.to_string();
+                self.current_symbol = Some(symbol.clone());
+                self.send_command(ApiCommand::CompanyProfile(symbol));
+            }
+        }
+
+        /// Reads a broker statement typed in as PATH [PROFILE] into the portfolio
+        pub fn import(&mut self) -> Result<String, Error> {
+            let input = self.search_string.trim();
+            // The profile is the last word if there's a profile by that name, so paths can have spaces
+            let (path, profile_name) = match input.rsplit_once(' ') {
+                Some((path, name)) if self.import_profiles.contains_key(name) => {
+                    (path.trim(), name)
+                }
+                _ => (input, "default"),
+            };
+            if path.is_empty() {
+                return Err(anyhow::anyhow!(
+                    "Type the path of a CSV statement and optionally a profile, e.g. trades.csv mybroker"
+                ));
+            }
+            let profile = self.import_profiles.get(profile_name).with_context(|| {
+                format!("No import profile called {profile_name} in config.json")
+            })?;
+            if self.companies.is_empty() {
+                return Err(anyhow::anyhow!(
+                    "The symbol list hasn't loaded yet, so the symbols can't be checked. Try again in a moment"
+                ));
+            }
+            let known_symbols = self
+                .companies
+                .iter()
+                .filter_map(|company| company.rsplit(" : ").next())
+                .map(str::to_string)
+                .collect::<HashSet<_>>();
+            let transactions = import_csv(
+                Path::new(path),
+                profile,
+                self.portfolio.next_id(),
+                &known_symbols,
+            )?;
+            let count = transactions.len();
+            self.portfolio.add_all(transactions)?;
+            Ok(format!(
+                "Imported {count} transactions from {path} with profile {profile_name}. Choose Portfolio to see the lots"
+            ))
+        }
+
+        /// The symbol in the search box, or the last one looked at if nothing's typed. It
+        /// becomes the current symbol, and with neither the Results window asks for one.
+        fn symbol_or_current(&mut self) -> Option<String> {
+            let symbol = match self.search_string.trim() {
+                "" => self.current_symbol.clone(),
+                symbol => Some(symbol.to_uppercase()),
+            };
+            match &symbol {
+                Some(symbol) => self.current_symbol = Some(symbol.clone()),
+                None => {
+                    self.result_view = ResultView::Text;
+                    self.current_content = "Type a symbol first, e.g. AAPL".to_string();
+                }
+            }
+            symbol
+        }
+
+        /// Asks for the current category's news from after the newest item already there
+        pub fn refresh_news(&mut self) {
+            let category = self.news.category;
+            self.send_command(ApiCommand::MarketNews(category, self.news.min_id(category)));
+        }
+
+        pub fn refresh_portfolio(&mut self) {
+            if self.portfolio.report.open.is_empty() {
+                return;
+            }
+            // Needed with or without a base currency, to tell when a lot was paid in another one.
+            // Symbols from other markets aren't in the list, so their profile says.
+            let mut unknown = Vec::new();
+            for symbol in self.portfolio.symbols() {
+                match self.currencies.get(&symbol) {
+                    Some(currency) => {
+                        self.portfolio.currencies.insert(symbol, currency.clone());
+                    }
+                    None if !self.portfolio.currencies.contains_key(&symbol) => {
+                        unknown.push(symbol)
+                    }
+                    None => {}
+                }
+            }
+            self.send_command(ApiCommand::PortfolioQuotes(
+                self.portfolio.symbols(),
+                self.portfolio.base_currency.clone(),
+                unknown,
+            ));
+        }
+
+        /// Letters typed while the Watchlist window is selected:
+        /// a adds the symbol in the search box, d deletes, K and J move the selected symbol, r refreshes
+        pub fn watchlist_key(&mut self, c: char) {
+            let res = match c {
+                'a' => {
+                    let symbol = match self.search_string.trim() {
+                        "" => self.quote_symbol.clone().unwrap_or_default(),
+                        symbol => symbol.to_string(),
+                    };
+                    let res = self.watchlist.add(&symbol);
+                    self.refresh_watchlist(true);
+                    res
+                }
+                'd' => self.watchlist.remove_selected(),
+                'K' => self.watchlist.move_up(),
+                'J' => self.watchlist.move_down(),
+                'r' => {
+                    self.refresh_watchlist(true);
+                    Ok(())
+                }
+                _ => Ok(()),
+            };
+            if let Err(e) = res {
+                self.result_view = ResultView::Text;
+                self.current_content = e.to_string();
+            }
+        }
+
+        /// Asks for quotes for the whole watchlist every WATCHLIST_REFRESH_SECS, or right away if forced.
+        /// Doesn't wait if the FinanceClient is busy, the next tick will try again.
+        pub fn refresh_watchlist(&mut self, force: bool) {
+            if self.watchlist.symbols.is_empty() {
+                return;
+            }
+            let due = self.last_watchlist_request.is_none_or(|last_request| {
+                last_request.elapsed() >= Duration::from_secs(WATCHLIST_REFRESH_SECS)
+            });
+            if (due || force)
+                && self
+                    .api_sender
+                    .try_send(ApiCommand::WatchlistQuotes(self.watchlist.symbols.clone()))
+                    .is_ok()
+            {
+                self.last_watchlist_request = Some(Instant::now());
+            }
+        }
+
+        /// Keeps the quote up to date while the user has the Quote choice selected
+        pub fn refresh_quote(&mut self) {
+            if self.api_choice() != ApiChoice::Quote || self.waiting_for_result {
+                return;
+            }
+            let (Some(symbol), Some(last_request)) = (&self.quote_symbol, self.last_quote_request)
+            else {
+                return;
+            };
+            // A background refresh, so it neither waits for room in the channel nor puts
+            // the Results title into waiting
+            if last_request.elapsed() >= Duration::from_secs(QUOTE_REFRESH_SECS)
+                && self
+                    .api_sender
+                    .try_send(ApiCommand::Quote(symbol.clone()))
+                    .is_ok()
+            {
+                self.last_quote_request = Some(Instant::now());
+            }
+        }
+
+        /// A reply has come in, so the Results title stops saying it's waiting for one
+        fn finish_request(&mut self) {
+            self.waiting_for_result = false;
+            self.api_status = None;
+        }
+
+        /// Puts a reply in the Results window
+        fn show(&mut self, view: ResultView) {
+            self.finish_request();
+            self.result_view = view;
         }
 
         pub fn send_command(&mut self, command: ApiCommand) {
             self.waiting_for_result = true;
+            self.cache_age = None;
             self.api_sender.send(command).unwrap();
         }
 
-        pub fn draw_terminal(&mut self, terminal: &mut Terminal<CrosstermBackend<Stdout>>) {
+        pub fn draw_terminal<B: Backend>(&mut self, terminal: &mut Terminal<B>) {
             terminal
                 .draw(|f| {
                     // First 2 big blocks
@@ -311,6 +1338,13 @@ pub mod app {
                         )
                         .split(f.size());
 
+                    let results_and_watchlist = Layout::default()
+                        .direction(Direction::Horizontal)
+                        .constraints(
+                            [Constraint::Percentage(65), Constraint::Percentage(35)].as_ref(),
+                        )
+                        .split(top_and_bottom[1]);
+
                     // 2 Rects
                     let api_and_search_box = Layout::default()
                         .direction(Direction::Horizontal)
@@ -321,15 +1355,16 @@ pub mod app {
 
                     let highlighted = Style::default().fg(Color::LightYellow);
                     let unhighlighted = Style::default();
-                    let api_choice_border_style = match self.current_window {
-                        Window::ApiChoice => highlighted,
-                        Window::Results => unhighlighted,
-                    };
-
-                    let results_border_style = match self.current_window {
-                        Window::Results => highlighted,
-                        Window::ApiChoice => unhighlighted,
+                    let border_style = |window: Window| {
+                        if self.current_window == window {
+                            highlighted
+                        } else {
+                            unhighlighted
+                        }
                     };
+                    let api_choice_border_style = border_style(Window::ApiChoice);
+                    let results_border_style = border_style(Window::Results);
+                    let watchlist_border_style = border_style(Window::Watchlist);
 
                     // Api choices: top left block
                     let api_choices = make_table(self.all_choices()).block(
@@ -346,41 +1381,220 @@ pub mod app {
                         .alignment(Alignment::Center)
                         .wrap(Wrap { trim: true });
 
-                    let results_title = if self.waiting_for_result { "Waiting for results" } else { "Results" };
-                    let results = Paragraph::new(self.current_content.clone())
-                        .block(
-                            Block::default()
-                                .title(results_title)
-                                .borders(Borders::ALL)
-                                .border_style(results_border_style),
-                        )
-                        .style(Style::default().fg(Color::White).bg(Color::Black))
-                        .alignment(Alignment::Center)
-                        .wrap(Wrap { trim: true });
+                    let mut results_title = match (&self.result_view, self.waiting_for_result) {
+                        (_, true) => match self.api_status {
+                            Some(api_status) => format!("Waiting for results ({api_status})"),
+                            None => "Waiting for results".to_string(),
+                        },
+                        (ResultView::Text, false) => "Results".to_string(),
+                        (ResultView::Chart(chart), false) => format!(
+                            "Results: {} {} ({} chart, m to switch)",
+                            chart.symbol, chart.resolution, chart.mode
+                        ),
+                        (ResultView::MarketNews, false) => format!(
+                            "Results: {} news, {} new (c category, r refresh, n/p or PageDown/PageUp page)",
+                            self.news.category,
+                            self.news.current().map_or(0, |list| list.new_count())
+                        ),
+                        (ResultView::CompanyNews(symbol, _), false) => format!(
+                            "Results: {symbol} news (n/p or PageDown/PageUp page)"
+                        ),
+                        (ResultView::Portfolio, false) => format!(
+                            "Results: Portfolio, {}{} (d delete, u undo, m method, g gains, r refresh)",
+                            self.portfolio.method,
+                            self.portfolio.status_label()
+                        ),
+                        (ResultView::Financials(financials), false) => format!(
+                            "Results: {} basic financials (up/down pick a series, p for {})",
+                            financials.financials.symbol,
+                            match financials.period {
+                                Period::Annual => Period::Quarterly,
+                                Period::Quarterly => Period::Annual,
+                            }
+                        ),
+                        (ResultView::InsiderSentiment(sentiment), false) => format!(
+                            "Results: {} insider sentiment (MSPR) {} to {}",
+                            sentiment.symbol, sentiment.from, sentiment.to
+                        ),
+                        (ResultView::InsiderTransactions(table), false) => format!(
+                            "Results: {} insider transactions, {} (a all, b buys, s sells, c code, i insider)",
+                            table.symbol, table.filter
+                        ),
+                        (ResultView::FinancialsReported(view), false) => format!(
+                            "Results: {} {}, {} (s statement, n/o newer/older, v compare)",
+                            view.symbol,
+                            view.statement,
+                            view.shown_label()
+                        ),
+                        (ResultView::SecFilings(list), false) => format!(
+                            "Results: {} SEC filings {} to {}, {} (f form)",
+                            list.symbol,
+                            list.from,
+                            list.to,
+                            list.form_name()
+                        ),
+                        (ResultView::Earnings(table), false) => format!(
+                            "Results: Earnings {} to {}, {} (w to switch)",
+                            table.from,
+                            table.to,
+                            table.filter_name()
+                        ),
+                        (ResultView::Peers(list), false) => format!(
+                            "Results: {} peers (Enter for a profile, c to compare)",
+                            list.symbol
+                        ),
+                        (ResultView::Comparison(table), false) => format!(
+                            "Results: {} vs {} others by {} (s sorts, r reverses)",
+                            table.symbol,
+                            table.rows.len().saturating_sub(1),
+                            table.sort.to_string().to_lowercase()
+                        ),
+                        (ResultView::Ipos(table), false) => format!(
+                            "Results: IPOs {} to {}, {}, by {} (s sort, r reverse, f status)",
+                            table.from,
+                            table.to,
+                            table.status_name(),
+                            table.sort
+                        ),
+                        (ResultView::EpsSurprises(history), false) => format!(
+                            "Results: {} EPS surprises, {}",
+                            history.symbol,
+                            history.streak()
+                        ),
+                        (ResultView::Recommendations(chart), false) => format!(
+                            "Results: {} recommendation trends, {}",
+                            chart.symbol,
+                            chart.summary()
+                        ),
+                        (ResultView::Gains, false) => format!(
+                            "Results: Realized gains, {} (e to export, p for portfolio)",
+                            self.portfolio.method
+                        ),
+                    };
+                    if let (Some(age), false) = (self.cache_age, self.waiting_for_result) {
+                        results_title.push_str(&format!(" (cached {} ago)", format_age(age)));
+                    }
+                    let results_block = Block::default()
+                        .title(results_title)
+                        .borders(Borders::ALL)
+                        .border_style(results_border_style);
 
                     f.render_widget(api_choices, api_and_search_box[0]);
                     f.render_widget(search_area, api_and_search_box[1]);
-                    f.render_widget(results, top_and_bottom[1]);
+                    match &self.result_view {
+                        ResultView::Text => {
+                            let results = Paragraph::new(self.current_content.clone())
+                                .block(results_block)
+                                .style(Style::default().fg(Color::White).bg(Color::Black))
+                                .alignment(Alignment::Center)
+                                .wrap(Wrap { trim: true });
+                            f.render_widget(results, results_and_watchlist[0]);
+                        }
+                        ResultView::Chart(chart) => {
+                            chart.render(f, results_and_watchlist[0], results_block);
+                        }
+                        ResultView::Portfolio => {
+                            self.portfolio
+                                .render(f, results_and_watchlist[0], results_block);
+                        }
+                        ResultView::MarketNews => {
+                            self.news.render(f, results_and_watchlist[0], results_block);
+                        }
+                        ResultView::Financials(financials) => {
+                            financials.render(f, results_and_watchlist[0], results_block);
+                        }
+                        ResultView::InsiderSentiment(sentiment) => {
+                            sentiment.render(f, results_and_watchlist[0], results_block);
+                        }
+                        ResultView::InsiderTransactions(table) => {
+                            table.render(f, results_and_watchlist[0], results_block);
+                        }
+                        ResultView::FinancialsReported(view) => {
+                            view.render(f, results_and_watchlist[0], results_block);
+                        }
+                        ResultView::SecFilings(list) => {
+                            list.render(f, results_and_watchlist[0], results_block);
+                        }
+                        ResultView::Recommendations(chart) => {
+                            chart.render(f, results_and_watchlist[0], results_block);
+                        }
+                        ResultView::Earnings(table) => {
+                            table.render(f, results_and_watchlist[0], results_block);
+                        }
+                        ResultView::EpsSurprises(history) => {
+                            history.render(f, results_and_watchlist[0], results_block);
+                        }
+                        ResultView::Ipos(table) => {
+                            table.render(f, results_and_watchlist[0], results_block);
+                        }
+                        ResultView::Peers(list) => {
+                            list.render(f, results_and_watchlist[0], results_block);
+                        }
+                        ResultView::Comparison(table) => {
+                            table.render(f, results_and_watchlist[0], results_block);
+                        }
+                        ResultView::CompanyNews(_, list) => {
+                            list.render(f, results_and_watchlist[0], results_block);
+                        }
+                        ResultView::Gains => {
+                            let gains = Paragraph::new(self.portfolio.report.to_string())
+                                .block(results_block)
+                                .style(Style::default().fg(Color::White).bg(Color::Black))
+                                .wrap(Wrap { trim: true });
+                            f.render_widget(gains, results_and_watchlist[0]);
+                        }
+                    }
+
+                    let watchlist_block = Block::default()
+                        .title("Watchlist")
+                        .borders(Borders::ALL)
+                        .border_style(watchlist_border_style);
+                    self.watchlist
+                        .render(f, results_and_watchlist[1], watchlist_block);
                 })
                 .unwrap();
         }
 
         pub fn new(api_sender: SyncSender<ApiCommand>, receiver: Receiver<Command>) -> Self {
+            // Files that can't be read shouldn't stop the tool from starting
+            let mut current_content = String::new();
+            let watchlist = Watchlist::load().unwrap_or_else(|e| {
+                current_content.push_str(&format!("{e}\n"));
+                Watchlist::default()
+            });
+            let portfolio = Portfolio::load().unwrap_or_else(|e| {
+                current_content.push_str(&format!("{e}\n"));
+                Portfolio::default()
+            });
             Self {
                 current_window: Window::ApiChoice,
                 api_choices: TotalApiChoices::default(),
                 current_market: "US".to_string(),
                 companies: Vec::new(),
-                current_content: String::new(),
+                currencies: HashMap::new(),
+                crypto_exchange: None,
+                crypto_symbols: Vec::new(),
+                current_content,
                 search_string: String::new(),
                 api_sender,
                 receiver,
-                waiting_for_result: false
+                waiting_for_result: false,
+                quote_symbol: None,
+                current_symbol: None,
+                last_quote_request: None,
+                result_view: ResultView::Text,
+                cache_age: None,
+                api_status: None,
+                watchlist,
+                last_watchlist_request: None,
+                portfolio,
+                news: MarketNewsFeeds::default(),
+                import_profiles: builtin_profiles(),
             }
         }
 
         // todo!() turn this into Tables: 3*3 and then later 4*4
-        pub fn all_choices(&self) -> Vec<Span> {
+        pub fn all_choices(&self) -> Vec<Span<'_>> {
             let choices = &self.api_choices.all_apis;
 
             choices
@@ -406,7 +1620,8 @@ pub mod app {
         pub fn switch_window(&mut self) {
             self.current_window = match self.current_window {
                 Window::ApiChoice => Window::Results,
-                Window::Results => Window::ApiChoice,
+                Window::Results => Window::Watchlist,
+                Window::Watchlist => Window::ApiChoice,
             }
         }
 
@@ -432,21 +1647,31 @@ pub mod app {
 
     /// Vec<StockSymbol>
     impl FinanceClient {
+        /// Sends what ok makes of a reply, or why there isn't one as text for the Results window
+        fn reply<T>(&self, res: Result<T, Error>, ok: impl FnOnce(T) -> Command) {
+            let command = match res {
+                Ok(value) => ok(value),
+                Err(e) => Command::ResultWindow(e.to_string()),
+            };
+            self.sender.send(command).unwrap();
+        }
+
         pub fn receive_command(&self) {
             let api_command = self.receiver.recv().unwrap();
+            // The market's symbols don't go in the Results window, so their age doesn't either
+            let shows_in_results = !matches!(
+                api_command,
+                ApiCommand::StockSymbols(_) | ApiCommand::WatchlistQuotes(_)
+            );
             match api_command {
                 ApiCommand::StockSymbols(url) => {
                     self.stock_symbols(url).unwrap();
                 }
                 ApiCommand::CompanyNews(company_symbol) => {
-                    let company_news_res = self.company_news(&company_symbol);
-                    let result_window_content = match company_news_res {
-                        Ok(company_stuff) => company_stuff,
-                        Err(e) => e.to_string(),
-                    };
-                    self.sender
-                        .send(Command::ResultWindow(result_window_content))
-                        .unwrap();
+                    self.reply(self.company_news(&company_symbol), |items| {
+                        let list = NewsList::new(items.into_iter().map(NewsItem::from).collect());
+                        Command::CompanyNews(company_symbol, list)
+                    })
                 }
                 ApiCommand::CompanyProfile(company_name) => {
                     let company_info = self.company_profile(company_name);
@@ -454,81 +1679,149 @@ pub mod app {
                         .send(Command::ResultWindow(company_info))
                         .unwrap();
                 },
-                ApiCommand::MarketNews => {
-                    let market_res = match self.market_news() {
-                        Ok(market_news) => market_news,
-                        Err(e) => e.to_string(),
-                    };
-                    self.sender.send(Command::ResultWindow(market_res)).unwrap();
+                ApiCommand::MarketNews(category, min_id) => self
+                    .reply(self.market_news(category, min_id), |items| {
+                        Command::MarketNews(category, items)
+                    }),
+                ApiCommand::Quote(company_symbol) => self
+                    .reply(self.quote(&company_symbol), |quote| {
+                        Command::ResultWindow(format!("{company_symbol}\n{quote}"))
+                    }),
+                ApiCommand::Candles(request) => self.reply(self.candles(&request), |candles| {
+                    Command::Chart(CandleChart::new(
+                        request.symbol,
+                        request.resolution,
+                        candles,
+                    ))
+                }),
+                ApiCommand::WatchlistQuotes(symbols) => {
+                    let quotes = self.quotes(symbols);
+                    self.sender.send(Command::WatchlistQuotes(quotes)).unwrap();
+                }
+                ApiCommand::PortfolioQuotes(symbols, base, unknown) => {
+                    let quotes = self.quotes(symbols);
+                    let rates = base.map(|base| self.forex_rates(&base).map_err(|e| e.to_string()));
+                    // A symbol whose profile doesn't come back just stays without a value in base
+                    let currencies = unknown
+                        .into_iter()
+                        .filter_map(|symbol| {
+                            let profile = self.provider.company_profile(&symbol).ok()?;
+                            Some((symbol, profile.currency))
+                        })
+                        .collect();
+                    self.sender
+                        .send(Command::PortfolioQuotes(quotes, rates, currencies))
+                        .unwrap();
+                }
+                ApiCommand::InsiderSentiment(range) => self
+                    .reply(self.insider_sentiment(&range), |months| {
+                        Command::InsiderSentiment(SentimentChart::new(range, months))
+                    }),
+                ApiCommand::InsiderTransactions(range) => {
+                    self.reply(self.insider_transactions(&range), |transactions| {
+                        Command::InsiderTransactions(InsiderTable::new(range, transactions))
+                    })
+                }
+                ApiCommand::Earnings(range) => self
+                    .reply(self.earnings_calendar(&range), |releases| {
+                        Command::Earnings(EarningsTable::new(range, releases))
+                    }),
+                ApiCommand::EpsSurprises(symbol, quarters) => self
+                    .reply(self.eps_surprises(&symbol, quarters), |surprises| {
+                        Command::EpsSurprises(EpsHistory::new(symbol, surprises))
+                    }),
+                ApiCommand::Peers(symbol) => self.reply(self.peers(&symbol), |peers| {
+                    Command::Peers(PeerList::new(symbol, peers))
+                }),
+                ApiCommand::CryptoExchanges => self.reply(self.crypto_exchanges(), |exchanges| {
+                    Command::ResultWindow(format!(
+                        "Crypto exchanges (type one to load its symbols):\n\n{}",
+                        exchanges.join("\n")
+                    ))
+                }),
+                ApiCommand::CryptoSymbols(exchange) => self
+                    .reply(self.crypto_symbols(&exchange), |symbols| {
+                        Command::CryptoSymbols(exchange, symbols)
+                    }),
+                ApiCommand::CryptoCandles(request) => {
+                    self.reply(self.crypto_candles(&request), |candles| {
+                        Command::Chart(CandleChart::new(
+                            request.symbol,
+                            request.resolution,
+                            candles,
+                        ))
+                    })
+                }
+                ApiCommand::ForexExchanges => self.reply(self.forex_exchanges(), |exchanges| {
+                    Command::ResultWindow(format!(
+                        "Forex exchanges (type one to see its pairs):\n\n{}",
+                        exchanges.join("\n")
+                    ))
+                }),
+                ApiCommand::ForexSymbols(exchange, filter) => {
+                    self.reply(self.forex_symbols(&exchange), |symbols| {
+                        Command::ResultWindow(symbols_text(&exchange, &symbols, filter.as_deref()))
+                    })
+                }
+                ApiCommand::ForexRates(request) => self.reply(
+                    self.forex_rates(request.base())
+                        .and_then(|rates| request.answer(&rates)),
+                    Command::ResultWindow,
+                ),
+                ApiCommand::ComparePeers(set) => self.reply(self.compare_peers(&set), |rows| {
+                    Command::Comparison(ComparisonTable::new(set.main_symbol().to_string(), rows))
+                }),
+                ApiCommand::Ipos(range) => self.reply(self.ipo_calendar(&range), |ipos| {
+                    Command::Ipos(IpoTable::new(range, ipos))
+                }),
+                ApiCommand::Recommendations(symbol) => self
+                    .reply(self.recommendations(&symbol), |periods| {
+                        Command::Recommendations(RecommendationChart::new(symbol, periods))
+                    }),
+                ApiCommand::SecFilings(range) => self.reply(self.sec_filings(&range), |filings| {
+                    Command::SecFilings(FilingsList::new(range, filings))
+                }),
+                ApiCommand::FinancialsReported(request) => self.reply(
+                    self.financials_reported(&request)
+                        .and_then(|filings| ReportedView::new(request, filings)),
+                    Command::FinancialsReported,
+                ),
+                ApiCommand::BasicFinancials(symbol) => self
+                    .reply(self.basic_financials(&symbol), |financials| {
+                        Command::Financials(Box::new(FinancialsView::new(financials)))
+                    }),
+            }
+            if let Some(age) = self.provider.take_cache_age() {
+                if shows_in_results {
+                    self.sender.send(Command::CacheAge(age)).unwrap();
                 }
             }
         }
 
-        pub fn new(sender: SyncSender<Command>, receiver: Receiver<ApiCommand>) -> Self {
-            // headers.insert("X-Finnhub-Token", HeaderValue::from_static(API_KEY));
-
-            Self { receiver, sender }
-        }
-
-        pub fn single_request<T: DeserializeOwned + Debug>(
-            &self,
-            url: String,
-            company_name: &str,
-        ) -> Result<T, Error> {
-            let text = self.get_text(url)?;
-            let finnhub_reply: T = serde_json::from_str(&text).with_context(|| {
-                format!(
-                    "Couldn't deserialize {company_name} into CompanyProfile struct.\nText from Finnhub: '{text}'"
-                )
-            })?;
-            Ok(finnhub_reply)
-        }
-
-        pub fn multi_request<T: DeserializeOwned + Debug>(
-            &self,
-            url: String,
-            company_name: &str,
-        ) -> Result<Vec<T>, Error> {
-            let text = self.get_text(url)?;
-
-            let finnhub_reply: Vec<T> = serde_json::from_str(&text).with_context(|| {
-                format!(
-                    "Couldn't deserialize {company_name} into CompanyProfile struct.\nText from Finnhub: '{text}'",
-                )
-            })?;
-            Ok(finnhub_reply)
+        pub fn new(
+            sender: SyncSender<Command>,
+            receiver: Receiver<ApiCommand>,
+            provider: Box<dyn MarketDataProvider>,
+        ) -> Self {
+            Self {
+                receiver,
+                sender,
+                provider,
+            }
         }
 
         pub fn company_profile(&self, company_name: String) -> String {
-            // /stock/profile?symbol=AAPL
-            let url = format!("{FINNHUB_URL}/stock/profile2?symbol={company_name}");
-            match self.single_request::<CompanyProfile>(url, &company_name) {
+            match self.provider.company_profile(&company_name) {
                 Ok(company_profile) => company_profile.to_string(),
                 Err(e) => e.to_string(),
             }
         }
 
-        pub fn get_text(&self, url: String) -> Result<String, Error> {
-            let url_with_key = format!("{url}&token={API_KEY}");
-            ureq::get(&url_with_key)
-                .set("X-Finnhub-Token", API_KEY)
-                .call()
-                .with_context(|| format!("Couldn't send through url {url}"))?
-                .into_string()
-                .with_context(|| "No text for some reason")
-        }
-
         pub fn stock_symbols(&self, current_market: String) -> Result<(), Error> {
-            let url = format!("{FINNHUB_URL}/stock/symbol?exchange={current_market}");
-            let res = self.get_text(url);
-            match res {
-                Ok(text) => {
-                    let stock_symbols: Result<Vec<StockSymbol>, Error> =
-                        serde_json::from_str(&text).map_err(|e| {
-                            anyhow::anyhow!(format!("Couldn't make any stock symbols: {e}"))
-                        });
+            match self.provider.stock_symbols(&current_market) {
+                Ok(stock_symbols) => {
                     self.sender
-                        .send(Command::StockSymbols(stock_symbols))
+                        .send(Command::StockSymbols(Ok(stock_symbols)))
                         .unwrap();
                     Ok(())
                 }
@@ -541,81 +1834,324 @@ pub mod app {
                     Ok(())
                 }
             }
-            // let text = match self.get_text(url) {
-            //     Ok(text) => text,
-            //     Err(e) => bail!(e.to_string())
-            // };
-            // //let mut new_file = File::create("stock_symbols.json")?;
-            // //write!(&mut new_file, "{}", text)?;
-            // let stock_symbols: Result<Vec<StockSymbol>, Error> = serde_json::from_str(&text)
-            //     .map_err(|e| anyhow::anyhow!(format!("Couldn't make any stock symbols: {e}")));
-            // self.sender.send(Command::StockSymbols(stock_symbols)).unwrap();
-            // Ok(())
-            //Ok(())
-            //self.stock_symbols(current_market)
-            //Ok(stock_symbols)
-        }
-
-        /// /stock/symbol?exchange=US
-        // pub fn stock_symbols_init(&self, current_market: String) -> Result<Vec<StockSymbol>, Error> {
-        //     let url = format!(
-        //         "{FINNHUB_URL}/stock/symbol?exchange={current_market}",
-        //     );
-        //     let company_info = self
-        //         .multi_request(url)
-        //         .unwrap()
-        //         .into_iter()
-        //         .map(|s| format!("{}: {}\n", s.description, s.display_symbol))
-        //         .collect::<Vec<String>>();
-
-        //     let text = self.get_text(url)?;
-        //     let mut new_file = File::create("stock_symbols.json")?;
-        //     write!(&mut new_file, "{}", text)?;
-        //     let stock_symbols: Vec<StockSymbol> = serde_json::from_str(&text).unwrap();
-        //     Ok(stock_symbols)
-        // }
+        }
+
+        /// company-news?symbol=AAPL&from=2021-09-01&to=2021-09-09
+        /// Required: date + symbol
+        pub fn company_news(&self, company_symbol: &str) -> Result<Vec<CompanyNews>, Error> {
+            let now = chrono::Utc::today().naive_utc();
+            let six_months = Months::new(6);
+            let six_months_ago = now - six_months;
+            let news_items = self
+                .provider
+                .company_news(company_symbol, six_months_ago, now);
+            match news_items {
+                Err(e) => Err(anyhow::anyhow!(format!(
+                    "Couldn't get news for company {company_symbol}: {e}"
+                ))),
+                Ok(items) if items.is_empty() => Err(anyhow::anyhow!(format!(
+                    "Couldn't get news for company {company_symbol}"
+                ))),
+                Ok(items) => Ok(items),
+            }
+        }
+
+        /// quote?symbol=AAPL
+        pub fn quote(&self, company_symbol: &str) -> Result<Quote, Error> {
+            let quote = self.provider.quote(company_symbol)?;
+            if quote.is_empty() {
+                Err(anyhow::anyhow!(format!(
+                    "Couldn't get a quote for symbol {company_symbol}"
+                )))
+            } else {
+                Ok(quote)
+            }
+        }
+
+        /// One quote per symbol, keeping the reason for any that didn't work
+        pub fn quotes(&self, symbols: Vec<String>) -> Vec<(String, Result<Quote, String>)> {
+            symbols
+                .into_iter()
+                .map(|symbol| {
+                    let quote = self.quote(&symbol).map_err(|e| e.to_string());
+                    (symbol, quote)
+                })
+                .collect()
+        }
+
+        /// stock/candle?symbol=AAPL&resolution=1&from=1679476980&to=1679649780
+        pub fn candles(&self, request: &CandleRequest) -> Result<Candles, Error> {
+            let CandleRequest {
+                symbol,
+                resolution,
+                from,
+                to,
+            } = request;
+            let from_timestamp = from.and_hms(0, 0, 0).timestamp();
+            let to_timestamp = to.and_hms(23, 59, 59).timestamp();
+            let candles =
+                self.provider
+                    .candles(symbol, *resolution, from_timestamp, to_timestamp)?;
+            if candles.is_empty() {
+                Err(anyhow::anyhow!(format!(
+                    "No candles for {symbol} between {from} and {to}"
+                )))
+            } else {
+                Ok(candles)
+            }
+        }
+
+        /// crypto/candle?symbol=BINANCE:BTCUSDT&resolution=D&from=1572651390&to=1575243390
+        pub fn crypto_candles(&self, request: &CandleRequest) -> Result<Candles, Error> {
+            let CandleRequest {
+                symbol,
+                resolution,
+                from,
+                to,
+            } = request;
+            let candles = self.provider.crypto_candles(
+                symbol,
+                *resolution,
+                from.and_hms(0, 0, 0).timestamp(),
+                to.and_hms(23, 59, 59).timestamp(),
+            )?;
+            if candles.is_empty() {
+                Err(anyhow::anyhow!(format!(
+                    "No candles for {symbol} between {from} and {to}, crypto symbols need their exchange in front like BINANCE:BTCUSDT"
+                )))
+            } else {
+                Ok(candles)
+            }
+        }
+
+        pub fn crypto_exchanges(&self) -> Result<Vec<String>, Error> {
+            let exchanges = self.provider.crypto_exchanges()?;
+            if exchanges.is_empty() {
+                Err(anyhow::anyhow!("No crypto exchanges"))
+            } else {
+                Ok(exchanges)
+            }
+        }
+
+        pub fn crypto_symbols(&self, exchange: &str) -> Result<Vec<CryptoSymbol>, Error> {
+            let symbols = self.provider.crypto_symbols(exchange)?;
+            if symbols.is_empty() {
+                Err(anyhow::anyhow!(format!(
+                    "No crypto symbols for exchange {exchange}"
+                )))
+            } else {
+                Ok(symbols)
+            }
+        }
+
+        pub fn basic_financials(&self, symbol: &str) -> Result<BasicFinancials, Error> {
+            let mut financials = self.provider.basic_financials(symbol)?;
+            if financials.is_empty() {
+                return Err(anyhow::anyhow!(format!("No basic financials for {symbol}")));
+            }
+            if financials.symbol.is_empty() {
+                financials.symbol = symbol.to_string();
+            }
+            Ok(financials)
+        }
+
+        pub fn earnings_calendar(&self, range: &DateRange) -> Result<Vec<EarningsRelease>, Error> {
+            let DateRange { from, to } = range;
+            let calendar = self.provider.earnings_calendar(*from, *to)?;
+            if calendar.earnings_calendar.is_empty() {
+                Err(anyhow::anyhow!(format!(
+                    "No earnings between {from} and {to}"
+                )))
+            } else {
+                Ok(calendar.earnings_calendar)
+            }
+        }
+
+        pub fn eps_surprises(
+            &self,
+            symbol: &str,
+            quarters: Option<u32>,
+        ) -> Result<Vec<EarningsSurprise>, Error> {
+            let mut surprises = self.provider.eps_surprises(symbol, quarters)?;
+            if surprises.is_empty() {
+                return Err(anyhow::anyhow!(format!("No EPS surprises for {symbol}")));
+            }
+            // Providers that ignore the limit send everything, newest first
+            if let Some(quarters) = quarters {
+                surprises.sort_by(|a, b| b.period.cmp(&a.period));
+                surprises.truncate(quarters as usize);
+            }
+            Ok(surprises)
+        }
+
+        pub fn peers(&self, symbol: &str) -> Result<Vec<String>, Error> {
+            let peers = self.provider.peers(symbol)?;
+            // Finnhub sends back just the symbol itself when it doesn't know any peers
+            if peers.iter().all(|peer| peer == symbol) {
+                Err(anyhow::anyhow!(format!("No peers for {symbol}")))
+            } else {
+                Ok(peers)
+            }
+        }
+
+        /// A profile and basic financials for each symbol, keeping whatever came back for
+        /// the ones where only one of the two worked
+        pub fn compare_peers(&self, set: &PeerSet) -> Result<Vec<PeerMetrics>, Error> {
+            let symbols = match set {
+                PeerSet::PeersOf(symbol) => {
+                    let mut symbols = vec![symbol.clone()];
+                    symbols.extend(
+                        self.peers(symbol)?
+                            .into_iter()
+                            .filter(|peer| peer != symbol),
+                    );
+                    symbols
+                }
+                PeerSet::Symbols(symbols) => symbols.clone(),
+            };
+            let rows = symbols
+                .into_iter()
+                .map(|symbol| {
+                    let profile = self.provider.company_profile(&symbol);
+                    let financials = self.basic_financials(&symbol);
+                    PeerMetrics::new(symbol, profile, financials)
+                })
+                .collect::<Vec<_>>();
+            if rows.iter().all(PeerMetrics::is_empty) {
+                let reason = rows
+                    .first()
+                    .and_then(|row| row.error.clone())
+                    .unwrap_or_default();
+                Err(anyhow::anyhow!(format!(
+                    "Couldn't get any of them: {reason}"
+                )))
+            } else {
+                Ok(rows)
+            }
+        }
+
+        pub fn forex_exchanges(&self) -> Result<Vec<String>, Error> {
+            let exchanges = self.provider.forex_exchanges()?;
+            if exchanges.is_empty() {
+                Err(anyhow::anyhow!("No forex exchanges"))
+            } else {
+                Ok(exchanges)
+            }
+        }
+
+        pub fn forex_symbols(&self, exchange: &str) -> Result<Vec<ForexSymbol>, Error> {
+            let symbols = self.provider.forex_symbols(exchange)?;
+            if symbols.is_empty() {
+                Err(anyhow::anyhow!(format!(
+                    "No forex pairs for exchange {exchange}"
+                )))
+            } else {
+                Ok(symbols)
+            }
+        }
+
+        /// Finnhub sometimes answers with a different base than the one asked for,
+        /// in which case the rates are worked out from it
+        pub fn forex_rates(&self, base: &str) -> Result<ForexRates, Error> {
+            let rates = self.provider.forex_rates(base)?;
+            if rates.base == base {
+                Ok(rates)
+            } else {
+                rates
+                    .rebase(base)
+                    .with_context(|| format!("No forex rates for {base}"))
+            }
+        }
+
+        pub fn ipo_calendar(&self, range: &DateRange) -> Result<Vec<Ipo>, Error> {
+            let DateRange { from, to } = range;
+            let calendar = self.provider.ipo_calendar(*from, *to)?;
+            if calendar.ipo_calendar.is_empty() {
+                Err(anyhow::anyhow!(format!("No IPOs between {from} and {to}")))
+            } else {
+                Ok(calendar.ipo_calendar)
+            }
+        }
+
+        pub fn recommendations(&self, symbol: &str) -> Result<Vec<RecommendationTrend>, Error> {
+            let periods = self.provider.recommendation_trends(symbol)?;
+            if periods.is_empty() {
+                Err(anyhow::anyhow!(format!(
+                    "No analyst recommendations for {symbol}"
+                )))
+            } else {
+                Ok(periods)
+            }
+        }
+
+        pub fn sec_filings(&self, range: &SymbolRange) -> Result<Vec<SecFiling>, Error> {
+            let SymbolRange { symbol, from, to } = range;
+            let filings = self.provider.sec_filings(symbol, *from, *to)?;
+            if filings.is_empty() {
+                Err(anyhow::anyhow!(format!(
+                    "No SEC filings for {symbol} between {from} and {to}"
+                )))
+            } else {
+                Ok(filings)
+            }
+        }
+
+        pub fn financials_reported(
+            &self,
+            request: &ReportedRequest,
+        ) -> Result<Vec<ReportedFiling>, Error> {
+            let symbol = &request.symbol;
+            let reported = self.provider.financials_reported(symbol, request.period)?;
+            if reported.data.is_empty() {
+                Err(anyhow::anyhow!(format!(
+                    "No {} financials as reported for {symbol}",
+                    request.period
+                )))
+            } else {
+                Ok(reported.data)
+            }
+        }
 
-        /// company-news?symbol=AAPL&from=2021-09-01&to=2021-09-09
-        /// Required: date + symbol
-        pub fn company_news(&self, company_symbol: &str) -> Result<String, Error> {
-            let now = chrono::Utc::today().naive_utc();
-            let six_months = Months::new(6);
-            let six_months_ago = now - six_months;
-            let url = format!("{FINNHUB_URL}/company-news/?symbol={company_symbol}&from={six_months_ago}&to={now}");
-            let news_items: Result<Vec<CompanyNews>, Error> =
-                self.multi_request(url, company_symbol);
-            match news_items {
-                Err(e) => Err(anyhow::anyhow!(format!(
-                    "Couldn't get news for company {company_symbol}: {e}"
-                ))),
-                Ok(items) if items.is_empty() => Err(anyhow::anyhow!(format!(
-                    "Couldn't get news for company {company_symbol}"
-                ))),
-                Ok(items) => Ok(items
-                    .into_iter()
-                    .map(|blurb| {
-                        let datetime = Utc.timestamp(blurb.datetime, 0).date_naive();
-                        format!("{} {} || {}\n\n", datetime, blurb.headline, blurb.source)
-                    })
-                    .take(5)
-                    .collect::<String>()),
+        pub fn insider_sentiment(
+            &self,
+            range: &SymbolRange,
+        ) -> Result<Vec<InsiderSentiment>, Error> {
+            let SymbolRange { symbol, from, to } = range;
+            let sentiment = self.provider.insider_sentiment(symbol, *from, *to)?;
+            if sentiment.data.is_empty() {
+                Err(anyhow::anyhow!(format!(
+                    "No insider sentiment for {symbol} between {from} and {to}"
+                )))
+            } else {
+                Ok(sentiment.data)
             }
         }
 
-        // todo! Let user decide on a topic - going with general for now
-        /// news?category=general
-        /// This parameter can be 1 of the following values general, forex, crypto, merger
-        pub fn market_news(&self) -> Result<String, Error> {
-            //news?category=general
-            let url = format!("{FINNHUB_URL}/news/?category=general&minId=7178340");
-            let text = self.get_text(url)?;
-            let market_news: Vec<MarketNews> = serde_json::from_str(&text)?;
-            let mut output_string = String::new();
-            market_news
-                .into_iter()
-                .take(5)
-                .for_each(|bit_of_news| output_string.push_str(&format!("{bit_of_news}\n")));
-            Ok(output_string)
+        pub fn insider_transactions(
+            &self,
+            range: &SymbolRange,
+        ) -> Result<Vec<InsiderTransaction>, Error> {
+            let SymbolRange { symbol, from, to } = range;
+            let transactions = self.provider.insider_transactions(symbol, *from, *to)?;
+            if transactions.data.is_empty() {
+                Err(anyhow::anyhow!(format!(
+                    "No insider transactions for {symbol} between {from} and {to}"
+                )))
+            } else {
+                Ok(transactions.data)
+            }
+        }
+
+        /// news?category=general&minId=7178340
+        /// Nothing coming back is fine, it just means there's nothing after min_id
+        pub fn market_news(
+            &self,
+            category: NewsCategory,
+            min_id: i64,
+        ) -> Result<Vec<MarketNews>, Error> {
+            self.provider
+                .market_news(&category.to_string(), min_id)
+                .map_err(|e| anyhow::anyhow!(format!("Couldn't get {category} news: {e}")))
         }
     }
 
@@ -627,6 +2163,25 @@ pub mod app {
         MarketNews,
         CompanyNews,
         GetMarket,
+        Quote,
+        Candles,
+        Portfolio,
+        Import,
+        BasicFinancials,
+        InsiderSentiment,
+        InsiderTransactions,
+        FinancialsReported,
+        SecFilings,
+        Recommendations,
+        Earnings,
+        EpsSurprises,
+        Ipos,
+        Peers,
+        ComparePeers,
+        ForexSymbols,
+        ForexRates,
+        CryptoSymbols,
+        CryptoCandles,
     }
 
     impl std::fmt::Display for ApiChoice {
@@ -639,10 +2194,110 @@ pub mod app {
                 MarketNews => "Market News",
                 CompanyNews => "Company News",
                 GetMarket => "Get Market",
+                Quote => "Quote",
+                Candles => "Candles",
+                Portfolio => "Portfolio",
+                Import => "Import CSV",
+                BasicFinancials => "Basic Financials",
+                InsiderSentiment => "Insider Sentiment",
+                InsiderTransactions => "Insider Transactions",
+                FinancialsReported => "Reported Financials",
+                SecFilings => "SEC Filings",
+                Recommendations => "Recommendations",
+                Earnings => "Earnings Calendar",
+                EpsSurprises => "EPS Surprises",
+                Ipos => "IPO Calendar",
+                Peers => "Peers",
+                ComparePeers => "Compare Peers",
+                ForexSymbols => "Forex Symbols",
+                ForexRates => "Forex Rates",
+                CryptoSymbols => "Crypto Symbols",
+                CryptoCandles => "Crypto Candles",
             };
             write!(f, "{}", output)
         }
     }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn date(text: &str) -> NaiveDate {
+            NaiveDate::parse_from_str(text, "%Y-%m-%d").unwrap()
+        }
+
+        #[test]
+        fn candle_request_reads_every_part() {
+            let request = CandleRequest::parse("aapl w 2022-01-01 2022-06-30").unwrap();
+            assert_eq!(request.symbol, "AAPL");
+            assert_eq!(request.resolution, Resolution::Week);
+            assert_eq!(request.from, date("2022-01-01"));
+            assert_eq!(request.to, date("2022-06-30"));
+        }
+
+        #[test]
+        fn candle_request_defaults_depend_on_resolution() {
+            let today = Utc::today().naive_utc();
+            let daily = CandleRequest::parse("AAPL").unwrap();
+            assert_eq!(daily.resolution, Resolution::Day);
+            assert_eq!(daily.to, today);
+            assert_eq!(daily.from, today - Months::new(12));
+            let monthly = CandleRequest::parse("AAPL M").unwrap();
+            assert_eq!(monthly.from, today - Months::new(60));
+            let intraday = CandleRequest::parse("AAPL 5").unwrap();
+            assert_eq!(intraday.from, today - chrono::Duration::days(7));
+        }
+
+        #[test]
+        fn candle_request_rejects_bad_input() {
+            assert!(CandleRequest::parse("").is_err());
+            assert!(CandleRequest::parse("AAPL X").is_err());
+            assert!(CandleRequest::parse("AAPL D 2022-13-01").is_err());
+            assert!(CandleRequest::parse("AAPL D 2022-06-30 2022-01-01").is_err());
+        }
+
+        #[test]
+        fn symbol_range_falls_back_to_current_symbol() {
+            let range = SymbolRange::parse("2022-01-01 2022-12-31", 3, Some("TSLA")).unwrap();
+            assert_eq!(range.symbol, "TSLA");
+            assert_eq!(range.from, date("2022-01-01"));
+            assert_eq!(range.to, date("2022-12-31"));
+
+            let range = SymbolRange::parse("msft", 3, Some("TSLA")).unwrap();
+            assert_eq!(range.symbol, "MSFT");
+            assert_eq!(range.from, range.to - Months::new(3));
+
+            assert!(SymbolRange::parse("", 3, None).is_err());
+            assert!(SymbolRange::parse("TSLA 2022-12-31 2022-01-01", 3, None).is_err());
+        }
+
+        #[test]
+        fn date_range_defaults_around_today() {
+            let today = Utc::today().naive_utc();
+            let range = DateRange::parse("", 7, 30).unwrap();
+            assert_eq!(range.from, today - chrono::Duration::days(7));
+            assert_eq!(range.to, today + chrono::Duration::days(30));
+
+            // A FROM after today moves the default TO along with it
+            let from = today + chrono::Duration::days(100);
+            let range = DateRange::parse(&from.to_string(), 7, 30).unwrap();
+            assert_eq!(range.to, from + chrono::Duration::days(30));
+
+            let range = DateRange::parse("2022-01-01 2022-02-01", 7, 30).unwrap();
+            assert_eq!(range.from, date("2022-01-01"));
+            assert_eq!(range.to, date("2022-02-01"));
+
+            assert!(DateRange::parse("2022-02-01 2022-01-01", 7, 30).is_err());
+            assert!(DateRange::parse("yesterday", 7, 30).is_err());
+        }
+
+        #[test]
+        fn parse_date_names_what_it_couldnt_read() {
+            assert_eq!(parse_date("2023-02-01").unwrap(), date("2023-02-01"));
+            let error = parse_date("01/02/2023").unwrap_err().to_string();
+            assert_eq!(error, "Couldn't read 01/02/2023 as a date, use YYYY-MM-DD");
+        }
+    }
 }
 
 /// Structs and enums for the Finnhub API.
@@ -670,14 +2325,14 @@ pub mod api {
     #[derive(Clone, Debug, Serialize, Deserialize)]
     pub struct CompanyProfile {
         country: String,
-        currency: String,
+        pub currency: String,
         exchange: String,
         #[serde(rename = "finnhubIndustry")]
         industry: String,
         ipo: String,
         #[serde(rename = "marketCapitalization")]
-        market_capitalization: f64,
-        name: String,
+        pub market_capitalization: f64,
+        pub name: String,
         phone: String,
         #[serde(rename = "shareOutstanding")]
         shares_outstanding: f64,
@@ -772,17 +2427,19 @@ Url: {weburl}
     /// "source": "CNBC",
     /// "summary": "Shares of Square soared on Tuesday evening after posting better-than-expected quarterly results and strong growth in its consumer payments app.",
     /// "url": "https://www.cnbc.com/2020/08/04/square-sq-earnings-q2-2020.html"
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Clone, Debug, Serialize, Deserialize)]
     pub struct MarketNews {
         pub category: String,
         pub datetime: i64,
         pub headline: String,
-        //pub id: i64,
+        pub id: i64,
         //pub image: String,
         //pub related: String,
         pub source: String,
-        //pub summary: String,
-        //pub url: String,
+        #[serde(default)]
+        pub summary: String,
+        #[serde(default)]
+        pub url: String,
     }
 
     impl std::fmt::Display for MarketNews {
@@ -792,6 +2449,7 @@ Url: {weburl}
                 headline,
                 source,
                 datetime,
+                ..
             } = self;
             // 2017-07-14
             let datetime = Utc.timestamp(*datetime, 0).date_naive();
@@ -878,6 +2536,88 @@ Url: {weburl}
     //   "symbol": "AAPL"
     // }
 
+    #[derive(Clone, Debug, Default, Serialize, Deserialize)]
+    pub struct BasicFinancials {
+        #[serde(default)]
+        pub symbol: String,
+        #[serde(default)]
+        pub metric: Metric,
+        #[serde(default)]
+        pub series: Series,
+    }
+
+    impl BasicFinancials {
+        pub fn is_empty(&self) -> bool {
+            self.metric.other.is_empty()
+                && self.metric.market_capitalization.is_none()
+                && self.series.annual.is_empty()
+                && self.series.quarterly.is_empty()
+        }
+    }
+
+    /// The metrics the tool shows by name. Finnhub sends well over a hundred, and the
+    /// rest end up in other. Margins and returns are percentages, market cap is in millions.
+    #[derive(Clone, Debug, Default, Serialize, Deserialize)]
+    pub struct Metric {
+        #[serde(rename = "marketCapitalization")]
+        pub market_capitalization: Option<f64>,
+        #[serde(rename = "peTTM")]
+        pub pe_ttm: Option<f64>,
+        #[serde(rename = "pbAnnual")]
+        pub pb: Option<f64>,
+        #[serde(rename = "psTTM")]
+        pub ps_ttm: Option<f64>,
+        #[serde(rename = "epsTTM")]
+        pub eps_ttm: Option<f64>,
+        #[serde(rename = "dividendYieldIndicatedAnnual")]
+        pub dividend_yield: Option<f64>,
+        pub beta: Option<f64>,
+        #[serde(rename = "grossMarginTTM")]
+        pub gross_margin_ttm: Option<f64>,
+        #[serde(rename = "operatingMarginTTM")]
+        pub operating_margin_ttm: Option<f64>,
+        #[serde(rename = "pretaxMarginTTM")]
+        pub pretax_margin_ttm: Option<f64>,
+        #[serde(rename = "netProfitMarginTTM")]
+        pub net_margin_ttm: Option<f64>,
+        #[serde(rename = "roeTTM")]
+        pub roe_ttm: Option<f64>,
+        #[serde(rename = "roaTTM")]
+        pub roa_ttm: Option<f64>,
+        #[serde(rename = "roiTTM")]
+        pub roi_ttm: Option<f64>,
+        #[serde(rename = "52WeekHigh")]
+        pub week_52_high: Option<f64>,
+        #[serde(rename = "52WeekHighDate")]
+        pub week_52_high_date: Option<String>,
+        #[serde(rename = "52WeekLow")]
+        pub week_52_low: Option<f64>,
+        #[serde(rename = "52WeekLowDate")]
+        pub week_52_low_date: Option<String>,
+        #[serde(rename = "52WeekPriceReturnDaily")]
+        pub week_52_return: Option<f64>,
+        #[serde(rename = "10DayAverageTradingVolume")]
+        pub ten_day_average_volume: Option<f64>,
+        #[serde(flatten)]
+        pub other: std::collections::HashMap<String, serde_json::Value>,
+    }
+
+    /// Each series is keyed by its metric name, e.g. "currentRatio", newest period first
+    #[derive(Clone, Debug, Default, Serialize, Deserialize)]
+    pub struct Series {
+        #[serde(default)]
+        pub annual: std::collections::BTreeMap<String, Vec<SeriesPoint>>,
+        #[serde(default)]
+        pub quarterly: std::collections::BTreeMap<String, Vec<SeriesPoint>>,
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct SeriesPoint {
+        pub period: String,
+        // Now and then a period is there without a number
+        pub v: Option<f64>,
+    }
+
     // Insider Sentiment
 
     // {
@@ -914,6 +2654,68 @@ Url: {weburl}
     //   "symbol":"TSLA"
     // }
 
+    // Insider Transactions
+
+    // {
+    //   "data": [
+    //     {
+    //       "name": "Kirkhorn Zachary",
+    //       "share": 57234,
+    //       "change": -1250,
+    //       "filingDate": "2021-03-19",
+    //       "transactionDate": "2021-03-17",
+    //       "transactionCode": "S",
+    //       "transactionPrice": 655.81
+    //     }
+    //   ],
+    //   "symbol": "TSLA"
+    // }
+
+    #[derive(Clone, Debug, Default, Serialize, Deserialize)]
+    pub struct InsiderTransactions {
+        #[serde(default)]
+        pub data: Vec<InsiderTransaction>,
+        #[serde(default)]
+        pub symbol: String,
+    }
+
+    /// share is what the insider holds after the transaction. The codes are the SEC's
+    /// Form 4 ones: P purchase, S sale, A award, M option exercise, F tax withholding, G gift.
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct InsiderTransaction {
+        pub name: String,
+        #[serde(default)]
+        pub share: f64,
+        pub change: f64,
+        #[serde(rename = "filingDate")]
+        pub filing_date: String,
+        #[serde(rename = "transactionDate")]
+        pub transaction_date: String,
+        #[serde(rename = "transactionCode", default)]
+        pub transaction_code: String,
+        #[serde(rename = "transactionPrice", default)]
+        pub transaction_price: f64,
+    }
+
+    #[derive(Clone, Debug, Default, Serialize, Deserialize)]
+    pub struct InsiderSentimentData {
+        #[serde(default)]
+        pub data: Vec<InsiderSentiment>,
+        #[serde(default)]
+        pub symbol: String,
+    }
+
+    /// One month. mspr (monthly share purchase ratio) goes from -100 for all selling
+    /// to 100 for all buying, change is the net number of shares bought or sold.
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct InsiderSentiment {
+        pub symbol: String,
+        pub year: i32,
+        pub month: u32,
+        pub change: f64,
+        pub mspr: f64,
+    }
+
     // Financials As Reported
 
     // {
@@ -954,6 +2756,125 @@ Url: {weburl}
     //   "symbol": "AAPL"
     // }
 
+    #[derive(Clone, Debug, Default, Serialize, Deserialize)]
+    pub struct FinancialsReported {
+        #[serde(default)]
+        pub cik: String,
+        #[serde(default)]
+        pub data: Vec<ReportedFiling>,
+        #[serde(default)]
+        pub symbol: String,
+    }
+
+    /// One 10-K or 10-Q. quarter is 0 for annual reports, and the dates have a time after them.
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct ReportedFiling {
+        #[serde(rename = "accessNumber", default)]
+        pub access_number: String,
+        #[serde(default)]
+        pub symbol: String,
+        pub year: i32,
+        #[serde(default)]
+        pub quarter: u32,
+        #[serde(default)]
+        pub form: String,
+        #[serde(rename = "startDate", default)]
+        pub start_date: String,
+        #[serde(rename = "endDate", default)]
+        pub end_date: String,
+        #[serde(rename = "filedDate", default)]
+        pub filed_date: String,
+        #[serde(default)]
+        pub report: Report,
+    }
+
+    #[derive(Clone, Debug, Default, Serialize, Deserialize)]
+    pub struct Report {
+        #[serde(default)]
+        pub bs: Statement,
+        #[serde(default)]
+        pub cf: Statement,
+        #[serde(default)]
+        pub ic: Statement,
+    }
+
+    /// Every line item of a statement, in the order Finnhub sent them (by name when they come
+    /// as a map). Companies name their own XBRL concepts, so there's no fixed set of fields.
+    #[derive(Clone, Debug, Default, Serialize, Deserialize)]
+    #[serde(from = "RawStatement")]
+    pub struct Statement(pub Vec<LineItem>);
+
+    impl Statement {
+        pub fn get(&self, concept: &str) -> Option<&LineItem> {
+            self.0.iter().find(|item| item.concept == concept)
+        }
+
+        pub fn value(&self, concept: &str) -> Option<f64> {
+            self.get(concept).and_then(|item| item.value)
+        }
+    }
+
+    /// value is None when the filing has text like "N/A" instead of a number
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct LineItem {
+        pub concept: String,
+        pub label: String,
+        #[serde(default)]
+        pub unit: String,
+        pub value: Option<f64>,
+    }
+
+    /// Statements come as a map of concept to value like above, or as a list of
+    /// {"concept", "label", "unit", "value"} items
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum RawStatement {
+        Items(Vec<RawLineItem>),
+        Map(serde_json::Map<String, serde_json::Value>),
+    }
+
+    #[derive(Deserialize)]
+    struct RawLineItem {
+        #[serde(default)]
+        concept: String,
+        #[serde(default)]
+        label: String,
+        #[serde(default)]
+        unit: String,
+        #[serde(default)]
+        value: serde_json::Value,
+    }
+
+    impl From<RawStatement> for Statement {
+        fn from(raw: RawStatement) -> Self {
+            let items = match raw {
+                RawStatement::Items(items) => items
+                    .into_iter()
+                    .map(|item| LineItem {
+                        label: if item.label.is_empty() {
+                            item.concept.clone()
+                        } else {
+                            item.label
+                        },
+                        concept: item.concept,
+                        unit: item.unit,
+                        value: item.value.as_f64(),
+                    })
+                    .collect(),
+                RawStatement::Map(map) => map
+                    .into_iter()
+                    .map(|(concept, value)| LineItem {
+                        label: concept.clone(),
+                        concept,
+                        unit: String::new(),
+                        value: value.as_f64(),
+                    })
+                    .collect(),
+            };
+            Statement(items)
+        }
+    }
+
     // SEC Filings
 
     // [
@@ -980,6 +2901,27 @@ Url: {weburl}
     //   ...
     // ]
 
+    /// dates are "2020-02-27 00:00:00", and reportUrl is the document itself while
+    /// filingUrl is the index of everything filed with it
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct SecFiling {
+        #[serde(rename = "accessNumber", default)]
+        pub access_number: String,
+        #[serde(default)]
+        pub symbol: String,
+        #[serde(default)]
+        pub cik: String,
+        pub form: String,
+        #[serde(rename = "filedDate", default)]
+        pub filed_date: String,
+        #[serde(rename = "acceptedDate", default)]
+        pub accepted_date: String,
+        #[serde(rename = "reportUrl", default)]
+        pub report_url: String,
+        #[serde(rename = "filingUrl", default)]
+        pub filing_url: String,
+    }
+
     // Recommendation Trends
 
     // [
@@ -1003,6 +2945,58 @@ Url: {weburl}
     //   }
     // ]
 
+    /// How many analysts rate the stock each way in the month starting at period
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct RecommendationTrend {
+        #[serde(default)]
+        pub symbol: String,
+        pub period: String,
+        #[serde(rename = "strongBuy", default)]
+        pub strong_buy: u32,
+        #[serde(default)]
+        pub buy: u32,
+        #[serde(default)]
+        pub hold: u32,
+        #[serde(default)]
+        pub sell: u32,
+        #[serde(rename = "strongSell", default)]
+        pub strong_sell: u32,
+    }
+
+    // Earnings Surprises
+
+    // [
+    //   {
+    //     "actual": 1.88,
+    //     "estimate": 1.9744,
+    //     "period": "2023-03-31",
+    //     "quarter": 1,
+    //     "surprise": -0.0944,
+    //     "surprisePercent": -4.7812,
+    //     "symbol": "AAPL",
+    //     "year": 2023
+    //   },
+    //   ...
+    // ]
+
+    /// One quarter's reported EPS against what analysts expected. period is the last day of
+    /// the fiscal quarter, and the numbers are null when there's no estimate or no report yet.
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct EarningsSurprise {
+        #[serde(default)]
+        pub symbol: String,
+        pub period: String,
+        #[serde(default)]
+        pub year: i32,
+        #[serde(default)]
+        pub quarter: u32,
+        pub actual: Option<f64>,
+        pub estimate: Option<f64>,
+        pub surprise: Option<f64>,
+        #[serde(rename = "surprisePercent")]
+        pub surprise_percent: Option<f64>,
+    }
+
     // Earnings Calendar
 
     // {
@@ -1032,6 +3026,173 @@ Url: {weburl}
     //    ]
     // }
 
+    #[derive(Clone, Debug, Default, Serialize, Deserialize)]
+    pub struct EarningsCalendar {
+        #[serde(rename = "earningsCalendar", default)]
+        pub earnings_calendar: Vec<EarningsRelease>,
+    }
+
+    /// hour is bmo (before the market opens), amc (after it closes) or dmh (during market
+    /// hours), and the actuals are null until the company reports
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct EarningsRelease {
+        pub date: String,
+        #[serde(default)]
+        pub hour: String,
+        pub symbol: String,
+        #[serde(default)]
+        pub year: i32,
+        #[serde(default)]
+        pub quarter: u32,
+        #[serde(rename = "epsEstimate")]
+        pub eps_estimate: Option<f64>,
+        #[serde(rename = "epsActual")]
+        pub eps_actual: Option<f64>,
+        #[serde(rename = "revenueEstimate")]
+        pub revenue_estimate: Option<f64>,
+        #[serde(rename = "revenueActual")]
+        pub revenue_actual: Option<f64>,
+    }
+
+    // IPO Calendar
+
+    // {
+    //   "ipoCalendar": [
+    //     {
+    //       "date": "2020-06-12",
+    //       "exchange": "NYSE",
+    //       "name": "Vroom, Inc.",
+    //       "numberOfShares": 18750000,
+    //       "price": "18.00-20.00",
+    //       "status": "expected",
+    //       "symbol": "VRM",
+    //       "totalSharesValue": 431250000
+    //     },
+    //     ...
+    //   ]
+    // }
+
+    #[derive(Clone, Debug, Default, Serialize, Deserialize)]
+    pub struct IpoCalendar {
+        #[serde(rename = "ipoCalendar", default)]
+        pub ipo_calendar: Vec<Ipo>,
+    }
+
+    /// price is a range like "18.00-20.00" until the IPO is priced, and status is expected,
+    /// priced, withdrawn or filed. Anything not decided yet comes as null.
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct Ipo {
+        pub date: String,
+        pub exchange: Option<String>,
+        #[serde(default)]
+        pub name: String,
+        #[serde(rename = "numberOfShares")]
+        pub number_of_shares: Option<f64>,
+        pub price: Option<String>,
+        #[serde(default)]
+        pub status: String,
+        pub symbol: Option<String>,
+        #[serde(rename = "totalSharesValue")]
+        pub total_shares_value: Option<f64>,
+    }
+
+    // Forex Exchanges: Vec<String>
+
+    // ["oanda", "fxcm", "forex.com", "fxpro", "octafx", "ic markets", "pepperstone"]
+
+    // Forex Symbols
+
+    // [
+    //   {
+    //     "description": "Oanda EUR/USD",
+    //     "displaySymbol": "EUR/USD",
+    //     "symbol": "OANDA:EUR_USD"
+    //   },
+    //   ...
+    // ]
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct ForexSymbol {
+        pub description: String,
+        #[serde(rename = "displaySymbol")]
+        pub display_symbol: String,
+        pub symbol: String,
+    }
+
+    // Forex Rates
+
+    // {
+    //   "base": "USD",
+    //   "quote": {
+    //     "EUR": 0.9213,
+    //     "GBP": 0.7912,
+    //     "JPY": 149.87,
+    //     ...
+    //   }
+    // }
+
+    /// How much of each currency one of base buys
+    #[derive(Clone, Debug, Default, Serialize, Deserialize)]
+    pub struct ForexRates {
+        pub base: String,
+        #[serde(default)]
+        pub quote: std::collections::BTreeMap<String, f64>,
+    }
+
+    impl ForexRates {
+        pub fn rate(&self, currency: &str) -> Option<f64> {
+            if currency == self.base {
+                Some(1.0)
+            } else {
+                self.quote.get(currency).copied().filter(|rate| *rate > 0.0)
+            }
+        }
+
+        /// The same rates against another currency, through this base
+        pub fn rebase(&self, base: &str) -> Option<ForexRates> {
+            let through = self.rate(base)?;
+            let mut quote = self
+                .quote
+                .iter()
+                .map(|(currency, rate)| (currency.clone(), rate / through))
+                .collect::<std::collections::BTreeMap<_, _>>();
+            quote.insert(self.base.clone(), 1.0 / through);
+            quote.remove(base);
+            Some(ForexRates {
+                base: base.to_string(),
+                quote,
+            })
+        }
+
+        /// An amount in currency worth in base, as long as there's a rate for it
+        pub fn to_base(&self, amount: f64, currency: &str) -> Option<f64> {
+            Some(amount / self.rate(currency)?)
+        }
+    }
+
+    // Crypto Exchanges: Vec<String>
+
+    // ["KRAKEN", "HITBTC", "COINBASE", "GEMINI", "POLONIEX", "BINANCE", ...]
+
+    // Crypto Symbols
+
+    // [
+    //   {
+    //     "description": "Binance FRONT/ETH",
+    //     "displaySymbol": "FRONT/ETH",
+    //     "symbol": "BINANCE:FRONTETH"
+    //   },
+    //   ...
+    // ]
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct CryptoSymbol {
+        pub description: String,
+        #[serde(rename = "displaySymbol")]
+        pub display_symbol: String,
+        pub symbol: String,
+    }
+
     // Quote
 
     // {
@@ -1043,14 +3204,60 @@ Url: {weburl}
     //   "t": 1582641000
     // }
 
-    #[derive(Serialize, Deserialize)]
+    #[derive(Clone, Debug, Serialize, Deserialize)]
     pub struct Quote {
-        c: f64,
-        h: f64,
-        l: f64,
-        o: f64,
-        pc: f64,
-        t: i64,
+        /// Current price
+        pub c: f64,
+        /// High price of the day
+        pub h: f64,
+        /// Low price of the day
+        pub l: f64,
+        /// Open price of the day
+        pub o: f64,
+        /// Previous close price
+        pub pc: f64,
+        pub t: i64,
+    }
+
+    impl Quote {
+        /// Finnhub answers with all zeroes instead of an error for a symbol it doesn't know
+        pub fn is_empty(&self) -> bool {
+            self.c == 0.0 && self.pc == 0.0 && self.t == 0
+        }
+
+        pub fn change(&self) -> f64 {
+            self.c - self.pc
+        }
+
+        pub fn percent_change(&self) -> f64 {
+            if self.pc == 0.0 {
+                0.0
+            } else {
+                self.change() / self.pc * 100.0
+            }
+        }
+    }
+
+    impl std::fmt::Display for Quote {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            let Quote { c, h, l, o, pc, t } = self;
+            let change = self.change();
+            let percent_change = self.percent_change();
+            let time = Utc.timestamp(*t, 0).format("%Y-%m-%d %H:%M UTC");
+
+            let quote_info = format!(
+                "
+As of: {time}
+Current: {c:.2}
+Change: {change:+.2} ({percent_change:+.2}%)
+Open: {o:.2}
+High: {h:.2}
+Low: {l:.2}
+Previous close: {pc:.2}
+"
+            );
+            write!(f, "{}", quote_info)
+        }
     }
 
     // Candlestick Data
@@ -1089,7 +3296,118 @@ Url: {weburl}
     //   ]
     // }
 
-    // }
+    /// Finnhub only sends back `"s": "no_data"` when there's nothing in the range,
+    /// so every series defaults to empty
+    #[derive(Clone, Debug, Default, Serialize, Deserialize)]
+    pub struct Candles {
+        #[serde(default)]
+        pub c: Vec<f64>,
+        #[serde(default)]
+        pub h: Vec<f64>,
+        #[serde(default)]
+        pub l: Vec<f64>,
+        #[serde(default)]
+        pub o: Vec<f64>,
+        pub s: String,
+        #[serde(default)]
+        pub t: Vec<i64>,
+        #[serde(default)]
+        pub v: Vec<f64>,
+    }
+
+    impl Candles {
+        pub fn is_empty(&self) -> bool {
+            self.s != "ok" || self.t.is_empty()
+        }
+    }
+
+    /// Supported resolutions: 1, 5, 15, 30, 60, D, W, M
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum Resolution {
+        One,
+        Five,
+        Fifteen,
+        Thirty,
+        Sixty,
+        Day,
+        Week,
+        Month,
+    }
+
+    impl std::fmt::Display for Resolution {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            use Resolution::*;
+            let output = match self {
+                One => "1",
+                Five => "5",
+                Fifteen => "15",
+                Thirty => "30",
+                Sixty => "60",
+                Day => "D",
+                Week => "W",
+                Month => "M",
+            };
+            write!(f, "{}", output)
+        }
+    }
+
+    impl std::str::FromStr for Resolution {
+        type Err = anyhow::Error;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            use Resolution::*;
+            match s.to_uppercase().as_str() {
+                "1" => Ok(One),
+                "5" => Ok(Five),
+                "15" => Ok(Fifteen),
+                "30" => Ok(Thirty),
+                "60" => Ok(Sixty),
+                "D" => Ok(Day),
+                "W" => Ok(Week),
+                "M" => Ok(Month),
+                other => Err(anyhow::anyhow!(format!(
+                    "No resolution called {other}, try one of 1, 5, 15, 30, 60, D, W, M"
+                ))),
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn rates() -> ForexRates {
+            ForexRates {
+                base: "USD".to_string(),
+                quote: [("EUR".to_string(), 0.8), ("JPY".to_string(), 150.0)]
+                    .into_iter()
+                    .collect(),
+            }
+        }
+
+        #[test]
+        fn rebase_goes_through_the_old_base() {
+            let rates = rates().rebase("EUR").unwrap();
+            assert_eq!(rates.base, "EUR");
+            assert!(!rates.quote.contains_key("EUR"));
+            assert!((rates.rate("USD").unwrap() - 1.25).abs() < 1e-9);
+            assert!((rates.rate("JPY").unwrap() - 187.5).abs() < 1e-9);
+            assert_eq!(rates.rate("EUR"), Some(1.0));
+        }
+
+        #[test]
+        fn rebase_needs_a_rate_for_the_new_base() {
+            assert!(rates().rebase("GBP").is_none());
+        }
+
+        #[test]
+        fn to_base_divides_by_the_rate() {
+            let rates = rates();
+            assert_eq!(rates.to_base(80.0, "EUR"), Some(100.0));
+            assert_eq!(rates.to_base(80.0, "USD"), Some(80.0));
+            assert_eq!(rates.to_base(80.0, "GBP"), None);
+        }
+    }
 }
 
 // #[cfg(test)]
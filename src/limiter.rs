@@ -0,0 +1,150 @@
+//! Keeps requests inside Finnhub's limits. The free plan allows about 60 calls a minute
+//! and answers 429 Too Many Requests past that.
+
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// Calls a minute on Finnhub's free plan
+pub const FREE_TIER_CALLS_PER_MINUTE: u32 = 60;
+/// How many times a request is tried again after a 429 or a 5xx
+pub const MAX_RETRIES: u32 = 4;
+/// Backoff doubles from here on each retry unless Finnhub says how long to wait
+pub const BASE_BACKOFF: Duration = Duration::from_secs(1);
+pub const MAX_BACKOFF: Duration = Duration::from_secs(32);
+
+/// What's holding a request up, so the Results window can say so
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiStatus {
+    /// Waiting for the rate limiter before sending
+    Queued { wait: Duration },
+    /// Finnhub answered with an error status and the request will be sent again
+    Retrying {
+        status: u16,
+        attempt: u32,
+        wait: Duration,
+    },
+}
+
+impl std::fmt::Display for ApiStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApiStatus::Queued { wait } => {
+                write!(
+                    f,
+                    "queued by rate limit, sending in {}s",
+                    wait.as_secs().max(1)
+                )
+            }
+            ApiStatus::Retrying {
+                status,
+                attempt,
+                wait,
+            } => write!(
+                f,
+                "throttled: Finnhub answered {status}, retry {attempt}/{MAX_RETRIES} in {}s",
+                wait.as_secs().max(1)
+            ),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct TokenBucket {
+    capacity: f64,
+    // Can go below zero: each waiting request has already reserved its token
+    tokens: f64,
+    per_second: f64,
+    last_refill: Instant,
+}
+
+/// A token bucket that every request to the same API shares. Cloning it shares the bucket.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    bucket: Arc<Mutex<TokenBucket>>,
+}
+
+impl RateLimiter {
+    /// Allows bursts of up to half a minute's worth of calls
+    pub fn per_minute(calls: u32) -> Self {
+        let calls = calls.max(1) as f64;
+        let capacity = (calls / 2.0).max(1.0);
+        Self {
+            bucket: Arc::new(Mutex::new(TokenBucket {
+                capacity,
+                tokens: capacity,
+                per_second: calls / 60.0,
+                last_refill: Instant::now(),
+            })),
+        }
+    }
+
+    /// Takes a token and says how long to wait before using it
+    pub fn reserve(&self) -> Duration {
+        let mut bucket = self.bucket.lock().unwrap();
+        let now = Instant::now();
+        let refill = now.duration_since(bucket.last_refill).as_secs_f64() * bucket.per_second;
+        bucket.tokens = (bucket.tokens + refill).min(bucket.capacity);
+        bucket.last_refill = now;
+        bucket.tokens -= 1.0;
+        if bucket.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-bucket.tokens / bucket.per_second)
+        }
+    }
+}
+
+/// 1s, 2s, 4s... up to MAX_BACKOFF
+pub fn backoff(attempt: u32) -> Duration {
+    BASE_BACKOFF
+        .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+        .min(MAX_BACKOFF)
+}
+
+/// Retry-After in seconds. The HTTP date form isn't used by Finnhub.
+pub fn retry_after(header: Option<&str>) -> Option<Duration> {
+    header?.trim().parse().ok().map(Duration::from_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn burst_is_free_then_requests_wait() {
+        let limiter = RateLimiter::per_minute(60);
+        for _ in 0..30 {
+            assert_eq!(limiter.reserve(), Duration::ZERO);
+        }
+        let wait = limiter.reserve();
+        assert!(wait > Duration::from_millis(900) && wait <= Duration::from_secs(1));
+        // Each waiting request reserves its own token, so the next one waits longer
+        assert!(limiter.reserve() > wait);
+    }
+
+    #[test]
+    fn clones_share_the_bucket() {
+        let limiter = RateLimiter::per_minute(2);
+        let clone = limiter.clone();
+        assert_eq!(limiter.reserve(), Duration::ZERO);
+        assert!(clone.reserve() > Duration::ZERO);
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        assert_eq!(backoff(1), Duration::from_secs(1));
+        assert_eq!(backoff(2), Duration::from_secs(2));
+        assert_eq!(backoff(4), Duration::from_secs(8));
+        assert_eq!(backoff(10), MAX_BACKOFF);
+        assert_eq!(backoff(u32::MAX), MAX_BACKOFF);
+    }
+
+    #[test]
+    fn retry_after_reads_seconds_only() {
+        assert_eq!(retry_after(Some(" 7 ")), Some(Duration::from_secs(7)));
+        assert_eq!(retry_after(Some("Wed, 21 Oct 2015 07:28:00 GMT")), None);
+        assert_eq!(retry_after(None), None);
+    }
+}
@@ -0,0 +1,524 @@
+//! Buy and sell transactions kept as tax lots. Sells are matched against the lots
+//! bought before them, which gives the open lots and the realized gains.
+
+use anyhow::{Context, Error};
+use chrono::{Months, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+impl std::fmt::Display for Side {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let output = match self {
+            Side::Buy => "buy",
+            Side::Sell => "sell",
+        };
+        write!(f, "{}", output)
+    }
+}
+
+/// Which lots a sell uses up when it doesn't name one
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LotMethod {
+    /// Oldest lot first
+    #[default]
+    Fifo,
+    /// Newest lot first
+    Lifo,
+    /// Every sell names its lot
+    SpecificId,
+}
+
+impl LotMethod {
+    pub fn next(self) -> Self {
+        match self {
+            LotMethod::Fifo => LotMethod::Lifo,
+            LotMethod::Lifo => LotMethod::SpecificId,
+            LotMethod::SpecificId => LotMethod::Fifo,
+        }
+    }
+}
+
+impl std::fmt::Display for LotMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let output = match self {
+            LotMethod::Fifo => "FIFO",
+            LotMethod::Lifo => "LIFO",
+            LotMethod::SpecificId => "specific ID",
+        };
+        write!(f, "{}", output)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Transaction {
+    pub id: u64,
+    pub date: NaiveDate,
+    pub side: Side,
+    pub symbol: String,
+    pub quantity: f64,
+    /// Per share
+    pub price: f64,
+    #[serde(default)]
+    pub fees: f64,
+    /// For a sell, the id of the buy whose lot it comes out of
+    #[serde(default)]
+    pub lot: Option<u64>,
+    /// What price and fees are in, when a broker statement says
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub currency: Option<String>,
+}
+
+impl Transaction {
+    /// [buy|sell] SYMBOL QUANTITY PRICE [DATE] [FEES] [lot=N], e.g. "sell AAPL 5 180 2023-02-01 1"
+    /// for a sell with 1 in fees or "sell AAPL 5 180 lot=3" for one out of lot 3 today.
+    /// Without buy or sell it's a buy, which is how positions were typed in before there were sells.
+    pub fn parse(input: &str, id: u64) -> Result<Self, Error> {
+        let mut words = input.split_whitespace().collect::<Vec<_>>();
+        // The lot can go anywhere after the price, so it doesn't need a date and fees in front
+        let mut lot = None;
+        if let Some(index) = words
+            .iter()
+            .position(|word| word.to_lowercase().starts_with("lot="))
+        {
+            let number = &words.remove(index)[4..];
+            lot = Some(
+                number
+                    .parse()
+                    .with_context(|| format!("Couldn't read {number} as a lot number"))?,
+            );
+        }
+        let side = match words.first().map(|word| word.to_lowercase()).as_deref() {
+            Some("buy") => {
+                words.remove(0);
+                Side::Buy
+            }
+            Some("sell") => {
+                words.remove(0);
+                Side::Sell
+            }
+            _ => Side::Buy,
+        };
+        let [symbol, quantity, price, rest @ ..] = words.as_slice() else {
+            return Err(anyhow::anyhow!(
+                "Type a transaction as [buy|sell] SYMBOL QUANTITY PRICE [DATE] [FEES] [lot=N], e.g. sell AAPL 5 180 2023-02-01 lot=3"
+            ));
+        };
+        let quantity: f64 = quantity
+            .parse()
+            .with_context(|| format!("Couldn't read {quantity} as a quantity"))?;
+        let price: f64 = price
+            .parse()
+            .with_context(|| format!("Couldn't read {price} as a price"))?;
+        let date = match rest.first() {
+            Some(date) => NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .with_context(|| format!("Couldn't read {date} as a date, use YYYY-MM-DD"))?,
+            None => Utc::today().naive_utc(),
+        };
+        let fees: f64 = match rest.get(1) {
+            Some(fees) => fees
+                .parse()
+                .with_context(|| format!("Couldn't read {fees} as fees"))?,
+            None => 0.0,
+        };
+        if let Some(extra) = rest.get(2) {
+            return Err(anyhow::anyhow!(format!(
+                "Don't know what {extra} is, name a lot with lot=N"
+            )));
+        }
+        if quantity <= 0.0 || price < 0.0 || fees < 0.0 {
+            return Err(anyhow::anyhow!(
+                "Quantity has to be above 0 and price and fees can't be negative"
+            ));
+        }
+        Ok(Self {
+            id,
+            date,
+            side,
+            symbol: symbol.to_uppercase(),
+            quantity,
+            price,
+            fees,
+            lot,
+            currency: None,
+        })
+    }
+}
+
+/// What's left of a buy
+#[derive(Clone, Debug)]
+pub struct Lot {
+    /// The id of the buy it came from
+    pub id: u64,
+    pub symbol: String,
+    pub date: NaiveDate,
+    pub quantity: f64,
+    /// Per share, with the buy's fees spread over it
+    pub cost_basis: f64,
+    /// What the buy was paid in, when a broker statement says
+    pub currency: Option<String>,
+}
+
+impl Lot {
+    pub fn cost(&self) -> f64 {
+        self.quantity * self.cost_basis
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Term {
+    Short,
+    Long,
+}
+
+impl std::fmt::Display for Term {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let output = match self {
+            Term::Short => "short",
+            Term::Long => "long",
+        };
+        write!(f, "{}", output)
+    }
+}
+
+/// One sell against one lot. A sell that used up a few lots gives a few of these.
+#[derive(Clone, Debug)]
+pub struct RealizedGain {
+    pub symbol: String,
+    pub lot: u64,
+    pub quantity: f64,
+    pub acquired: NaiveDate,
+    pub sold: NaiveDate,
+    pub proceeds: f64,
+    pub cost: f64,
+    pub term: Term,
+}
+
+impl RealizedGain {
+    pub fn gain(&self) -> f64 {
+        self.proceeds - self.cost
+    }
+}
+
+/// Long term means held for more than a year
+pub fn term(acquired: NaiveDate, sold: NaiveDate) -> Term {
+    if sold > acquired + Months::new(12) {
+        Term::Long
+    } else {
+        Term::Short
+    }
+}
+
+/// Shares left over from rounding aren't worth keeping a lot open for
+const DUST: f64 = 1e-9;
+
+#[derive(Debug, Default)]
+pub struct LotReport {
+    pub open: Vec<Lot>,
+    pub realized: Vec<RealizedGain>,
+}
+
+impl LotReport {
+    pub fn total(&self, term: Term) -> f64 {
+        self.realized
+            .iter()
+            .filter(|gain| gain.term == term)
+            .map(RealizedGain::gain)
+            .sum()
+    }
+
+    /// The realized gains as CSV, one line per lot sold from
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("symbol,lot,quantity,acquired,sold,proceeds,cost,gain,term\n");
+        for gain in &self.realized {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{:.2},{:.2},{:.2},{}\n",
+                gain.symbol,
+                gain.lot,
+                gain.quantity,
+                gain.acquired,
+                gain.sold,
+                gain.proceeds,
+                gain.cost,
+                gain.gain(),
+                gain.term
+            ));
+        }
+        csv
+    }
+}
+
+impl std::fmt::Display for LotReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let short_term = self.total(Term::Short);
+        let long_term = self.total(Term::Long);
+        writeln!(f, "Short term: {short_term:+.2}")?;
+        writeln!(f, "Long term: {long_term:+.2}")?;
+        writeln!(f, "Total: {:+.2}", short_term + long_term)?;
+        writeln!(f)?;
+        if self.realized.is_empty() {
+            return writeln!(f, "Nothing sold yet");
+        }
+        for gain in &self.realized {
+            writeln!(
+                f,
+                "{} {} {} from lot {} (bought {}): {:.2} - {:.2} = {:+.2} {} term",
+                gain.sold,
+                gain.symbol,
+                gain.quantity,
+                gain.lot,
+                gain.acquired,
+                gain.proceeds,
+                gain.cost,
+                gain.gain(),
+                gain.term
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Goes through the transactions in date order, using up lots as they're sold
+pub fn match_lots(transactions: &[Transaction], method: LotMethod) -> Result<LotReport, Error> {
+    let mut transactions = transactions.iter().collect::<Vec<_>>();
+    // Buys go before sells on the same day so a sell can use a lot bought that morning
+    transactions.sort_by_key(|transaction| {
+        (
+            transaction.date,
+            transaction.side == Side::Sell,
+            transaction.id,
+        )
+    });
+
+    let mut report = LotReport::default();
+    for transaction in transactions {
+        match transaction.side {
+            Side::Buy => report.open.push(Lot {
+                id: transaction.id,
+                symbol: transaction.symbol.clone(),
+                date: transaction.date,
+                quantity: transaction.quantity,
+                cost_basis: transaction.price + transaction.fees / transaction.quantity,
+                currency: transaction.currency.clone(),
+            }),
+            Side::Sell => sell(&mut report, transaction, method)?,
+        }
+    }
+    Ok(report)
+}
+
+fn sell(report: &mut LotReport, sale: &Transaction, method: LotMethod) -> Result<(), Error> {
+    // Indexes into report.open in the order they get used up
+    let candidates = report
+        .open
+        .iter()
+        .enumerate()
+        .filter(|(_, lot)| lot.symbol == sale.symbol)
+        .filter(|(_, lot)| sale.lot.is_none_or(|id| lot.id == id))
+        .map(|(index, _)| index)
+        .collect::<Vec<_>>();
+    let candidates = match (sale.lot, method) {
+        (Some(id), _) if candidates.is_empty() => {
+            return Err(anyhow::anyhow!(format!(
+                "Sell {} of {} on {} names lot {id}, which isn't an open {} lot",
+                sale.id, sale.symbol, sale.date, sale.symbol
+            )))
+        }
+        (None, LotMethod::SpecificId) => {
+            return Err(anyhow::anyhow!(format!(
+                "Sell {} of {} on {} needs lot=N to name its lot when using specific ID",
+                sale.id, sale.symbol, sale.date
+            )))
+        }
+        (None, LotMethod::Lifo) => candidates.into_iter().rev().collect(),
+        _ => candidates,
+    };
+
+    let available: f64 = candidates
+        .iter()
+        .map(|&index| report.open[index].quantity)
+        .sum();
+    if sale.quantity > available + DUST {
+        return Err(anyhow::anyhow!(format!(
+            "Sell {} on {} is for {} {} but only {} are held",
+            sale.id,
+            sale.date,
+            sale.quantity,
+            sale.symbol,
+            // An empty sum is -0
+            available.abs()
+        )));
+    }
+
+    let proceeds_per_share = sale.price - sale.fees / sale.quantity;
+    let mut left_to_sell = sale.quantity;
+    for index in candidates {
+        if left_to_sell <= DUST {
+            break;
+        }
+        let lot = &mut report.open[index];
+        let quantity = lot.quantity.min(left_to_sell);
+        lot.quantity -= quantity;
+        left_to_sell -= quantity;
+        report.realized.push(RealizedGain {
+            symbol: sale.symbol.clone(),
+            lot: lot.id,
+            quantity,
+            acquired: lot.date,
+            sold: sale.date,
+            proceeds: quantity * proceeds_per_share,
+            cost: quantity * lot.cost_basis,
+            term: term(lot.date, sale.date),
+        });
+    }
+    report.open.retain(|lot| lot.quantity > DUST);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(text: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(text, "%Y-%m-%d").unwrap()
+    }
+
+    fn transactions(inputs: &[&str]) -> Vec<Transaction> {
+        inputs
+            .iter()
+            .zip(1..)
+            .map(|(input, id)| Transaction::parse(input, id).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn parse_reads_date_fees_and_lot() {
+        let sell = Transaction::parse("sell aapl 5 180 2023-02-01 1 lot=3", 7).unwrap();
+        assert_eq!(sell.id, 7);
+        assert_eq!(sell.side, Side::Sell);
+        assert_eq!(sell.symbol, "AAPL");
+        assert_eq!(sell.quantity, 5.0);
+        assert_eq!(sell.price, 180.0);
+        assert_eq!(sell.date, date("2023-02-01"));
+        assert_eq!(sell.fees, 1.0);
+        assert_eq!(sell.lot, Some(3));
+
+        // lot=N can go anywhere without a date or fees in front of it
+        let sell = Transaction::parse("sell lot=2 AAPL 5 180", 1).unwrap();
+        assert_eq!(sell.lot, Some(2));
+        assert_eq!(sell.date, Utc::today().naive_utc());
+        assert_eq!(sell.fees, 0.0);
+    }
+
+    #[test]
+    fn parse_without_a_side_is_a_buy() {
+        let buy = Transaction::parse("MSFT 10 250", 1).unwrap();
+        assert_eq!(buy.side, Side::Buy);
+        assert_eq!(buy.lot, None);
+    }
+
+    #[test]
+    fn parse_rejects_bad_input() {
+        assert!(Transaction::parse("buy AAPL 5", 1).is_err());
+        assert!(Transaction::parse("buy AAPL five 180", 1).is_err());
+        assert!(Transaction::parse("buy AAPL 5 180 02/01/2023", 1).is_err());
+        assert!(Transaction::parse("sell AAPL 5 180 lot=x", 1).is_err());
+        assert!(Transaction::parse("buy AAPL 0 180", 1).is_err());
+        assert!(Transaction::parse("buy AAPL 5 -1", 1).is_err());
+        let error = Transaction::parse("sell AAPL 5 180 2023-02-01 1 3", 1).unwrap_err();
+        assert!(error.to_string().contains("lot=N"));
+    }
+
+    const HISTORY: [&str; 3] = [
+        "buy AAPL 10 100 2021-01-04",
+        "buy AAPL 10 150 2022-06-01",
+        "sell AAPL 15 200 2022-09-01",
+    ];
+
+    #[test]
+    fn fifo_sells_the_oldest_lot_first() {
+        let report = match_lots(&transactions(&HISTORY), LotMethod::Fifo).unwrap();
+        assert_eq!(report.realized.len(), 2);
+        assert_eq!(report.realized[0].lot, 1);
+        assert_eq!(report.realized[0].quantity, 10.0);
+        assert_eq!(report.realized[0].term, Term::Long);
+        assert_eq!(report.realized[1].lot, 2);
+        assert_eq!(report.realized[1].term, Term::Short);
+        assert_eq!(report.open.len(), 1);
+        assert_eq!(report.open[0].id, 2);
+        assert_eq!(report.open[0].quantity, 5.0);
+        assert_eq!(report.total(Term::Long), 1000.0);
+        assert_eq!(report.total(Term::Short), 250.0);
+    }
+
+    #[test]
+    fn lifo_sells_the_newest_lot_first() {
+        let report = match_lots(&transactions(&HISTORY), LotMethod::Lifo).unwrap();
+        assert_eq!(report.realized[0].lot, 2);
+        assert_eq!(report.realized[1].lot, 1);
+        assert_eq!(report.open[0].id, 1);
+        assert_eq!(report.open[0].quantity, 5.0);
+    }
+
+    #[test]
+    fn specific_id_needs_a_lot() {
+        assert!(match_lots(&transactions(&HISTORY), LotMethod::SpecificId).is_err());
+        let report = match_lots(
+            &transactions(&[HISTORY[0], HISTORY[1], "sell AAPL 4 200 2022-09-01 lot=2"]),
+            LotMethod::SpecificId,
+        )
+        .unwrap();
+        assert_eq!(report.realized.len(), 1);
+        assert_eq!(report.realized[0].lot, 2);
+        assert_eq!(report.open[0].quantity, 10.0);
+        assert_eq!(report.open[1].quantity, 6.0);
+    }
+
+    #[test]
+    fn fees_go_into_cost_and_proceeds() {
+        let report = match_lots(
+            &transactions(&[
+                "buy AAPL 10 100 2022-01-03 10",
+                "sell AAPL 10 110 2022-02-01 5",
+            ]),
+            LotMethod::Fifo,
+        )
+        .unwrap();
+        assert!((report.realized[0].cost - 1010.0).abs() < 1e-9);
+        assert!((report.realized[0].proceeds - 1095.0).abs() < 1e-9);
+        assert!(report.open.is_empty());
+    }
+
+    #[test]
+    fn same_day_buy_can_be_sold() {
+        // The sell comes first in the list but the buy is matched before it
+        let report = match_lots(
+            &transactions(&["sell AAPL 5 110 2022-02-01", "buy AAPL 5 100 2022-02-01"]),
+            LotMethod::Fifo,
+        )
+        .unwrap();
+        assert_eq!(report.realized.len(), 1);
+    }
+
+    #[test]
+    fn overselling_and_unknown_lots_fail() {
+        let error = match_lots(
+            &transactions(&[HISTORY[0], "sell AAPL 11 200 2022-09-01"]),
+            LotMethod::Fifo,
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("only 10 are held"));
+        assert!(match_lots(
+            &transactions(&[HISTORY[0], "sell AAPL 1 200 2022-09-01 lot=9"]),
+            LotMethod::Fifo,
+        )
+        .is_err());
+        assert!(match_lots(&transactions(&["sell MSFT 1 200"]), LotMethod::Fifo).is_err());
+    }
+}
@@ -1,11 +1,25 @@
-use std::sync::mpsc::sync_channel;
+use std::{sync::mpsc::sync_channel, time::Duration};
 
-use finance_tool::app::{handle_event, FinanceClient, State};
+use finance_tool::{
+    app::{handle_event, Command, FinanceClient, State},
+    cache::CachedProvider,
+    config::{cache_dir, resolve_api_key, Args, Config},
+    limiter::{RateLimiter, FREE_TIER_CALLS_PER_MINUTE},
+    provider::{FinnhubProvider, FixtureProvider, MarketDataProvider},
+};
 use tui::{backend::CrosstermBackend, Terminal};
 
 // const COMPANY_STR: &str = include_str!("../company_symbols.json");
 
 fn main() {
+    // Checked before the terminal is taken over so the message can be read
+    let args = match Args::parse(std::env::args()) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(2);
+        }
+    };
     let stdout = std::io::stdout();
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend).unwrap();
@@ -16,10 +30,50 @@ fn main() {
     let (api_sender, api_receiver) = sync_channel(2);
 
     let mut state = State::new(api_sender, command_receiver);
+    // Problems with the key or config get shown in the Results window instead of panicking.
+    // A broken config.json is only a warning, since the key can still come from the flag or
+    // the environment and everything else has defaults.
+    let (config, config_warning) = match Config::load() {
+        Ok(config) => (config, None),
+        Err(e) => (
+            Config::default(),
+            Some(format!("{e:#}\nUsing the default settings instead")),
+        ),
+    };
+    // Without a key the symbol list can't be fetched, but everything else still starts
+    let mut missing_key = None;
+    state.import_profiles.extend(config.import_profiles.clone());
+    let provider: Box<dyn MarketDataProvider> = match &args.fixtures {
+        Some(dir) => Box::new(FixtureProvider::new(dir)),
+        None => {
+            let api_key = resolve_api_key(&args, &config)
+                .map_err(|e| missing_key = Some(e.to_string()))
+                .ok();
+            let limiter = RateLimiter::per_minute(
+                config
+                    .calls_per_minute
+                    .unwrap_or(FREE_TIER_CALLS_PER_MINUTE),
+            );
+            let finnhub = Box::new(
+                FinnhubProvider::new(api_key, limiter).with_status(command_sender.clone()),
+            );
+            match cache_dir() {
+                Some(dir) => Box::new(CachedProvider::new(finnhub, dir, &config.cache_ttl)),
+                None => finnhub,
+            }
+        }
+    };
+
     let cloned = command_sender.clone();
-    let finance_client = FinanceClient::new(cloned, api_receiver);
+    let finance_client = FinanceClient::new(cloned, api_receiver, provider);
 
-    state.stock_symbols_init().unwrap();
+    if missing_key.is_none() {
+        state.stock_symbols_init().unwrap();
+    }
+    // Added to whatever State::new already put in the Results window
+    for message in [config_warning, missing_key].into_iter().flatten() {
+        state.current_content.push_str(&format!("{message}\n"));
+    }
     terminal.clear().unwrap();
     state.draw_terminal(&mut terminal);
 
@@ -27,6 +81,12 @@ fn main() {
         finance_client.receive_command();
     });
 
+    let tick_sender = command_sender.clone();
+    std::thread::spawn(move || loop {
+        std::thread::sleep(Duration::from_secs(1));
+        tick_sender.send(Command::Tick).unwrap();
+    });
+
     std::thread::spawn(move || loop {
         state.receive_command();
         state.check_self();
@@ -0,0 +1,456 @@
+//! The news reader for the Results window: a page of headlines to pick from with the
+//! selected story's summary and link underneath. Company news gets one list per request,
+//! and market news keeps one feed per category that remembers the newest id it has, so a
+//! refresh only asks Finnhub for what came after it and marks what's new.
+
+use std::{collections::HashMap, str::FromStr};
+
+use anyhow::Error;
+use chrono::{TimeZone, Utc};
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph, Row, Table, TableState, Wrap},
+    Frame,
+};
+
+use crate::api::{CompanyNews, MarketNews};
+
+/// Headlines on each page of the list
+pub const PAGE: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum NewsCategory {
+    #[default]
+    General,
+    Forex,
+    Crypto,
+    Merger,
+}
+
+impl NewsCategory {
+    const ALL: [NewsCategory; 4] = [
+        NewsCategory::General,
+        NewsCategory::Forex,
+        NewsCategory::Crypto,
+        NewsCategory::Merger,
+    ];
+
+    pub fn next(self) -> Self {
+        let index = Self::ALL
+            .iter()
+            .position(|category| *category == self)
+            .unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+/// What Finnhub calls them in ?category=
+impl std::fmt::Display for NewsCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let output = match self {
+            NewsCategory::General => "general",
+            NewsCategory::Forex => "forex",
+            NewsCategory::Crypto => "crypto",
+            NewsCategory::Merger => "merger",
+        };
+        write!(f, "{}", output)
+    }
+}
+
+impl FromStr for NewsCategory {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|category| category.to_string().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| {
+                anyhow::anyhow!(format!(
+                    "{s} isn't a news category, use general, forex, crypto or merger"
+                ))
+            })
+    }
+}
+
+/// One story, from company news or market news
+#[derive(Debug, Clone)]
+pub struct NewsItem {
+    pub id: i64,
+    pub datetime: i64,
+    pub headline: String,
+    pub source: String,
+    pub summary: String,
+    pub url: String,
+}
+
+impl From<CompanyNews> for NewsItem {
+    fn from(news: CompanyNews) -> Self {
+        Self {
+            id: news.id,
+            datetime: news.datetime,
+            headline: news.headline,
+            source: news.source,
+            summary: news.summary,
+            url: news.url,
+        }
+    }
+}
+
+impl From<MarketNews> for NewsItem {
+    fn from(news: MarketNews) -> Self {
+        Self {
+            id: news.id,
+            datetime: news.datetime,
+            headline: news.headline,
+            source: news.source,
+            summary: news.summary,
+            url: news.url,
+        }
+    }
+}
+
+/// Kept in State so the selection survives redraws
+#[derive(Debug, Clone, Default)]
+pub struct NewsList {
+    // Newest first
+    pub items: Vec<NewsItem>,
+    pub selected: usize,
+    // Items with an id above this are marked new. None when there's nothing to compare with.
+    pub new_after: Option<i64>,
+}
+
+impl NewsList {
+    pub fn new(items: Vec<NewsItem>) -> Self {
+        let mut list = Self {
+            items,
+            ..Default::default()
+        };
+        list.sort();
+        list
+    }
+
+    fn sort(&mut self) {
+        self.items
+            .sort_by(|a, b| b.datetime.cmp(&a.datetime).then(b.id.cmp(&a.id)));
+    }
+
+    pub fn is_new(&self, item: &NewsItem) -> bool {
+        self.new_after.is_some_and(|id| item.id > id)
+    }
+
+    pub fn new_count(&self) -> usize {
+        self.items.iter().filter(|item| self.is_new(item)).count()
+    }
+
+    pub fn pages(&self) -> usize {
+        self.items.len().div_ceil(PAGE).max(1)
+    }
+
+    pub fn up(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn down(&mut self) {
+        if self.selected + 1 < self.items.len() {
+            self.selected += 1;
+        }
+    }
+
+    /// To the top of the page before, or the top of this one if it's the first
+    pub fn page_up(&mut self) {
+        self.selected = (self.selected / PAGE).saturating_sub(1) * PAGE;
+    }
+
+    /// To the top of the next page, or the last item if this is the last page
+    pub fn page_down(&mut self) {
+        let next = (self.selected / PAGE + 1) * PAGE;
+        self.selected = next.min(self.items.len().saturating_sub(1));
+    }
+
+    /// n and p for the next and previous page, for keyboards without PageUp and PageDown
+    pub fn key(&mut self, c: char) {
+        match c {
+            'n' => self.page_down(),
+            'p' => self.page_up(),
+            _ => {}
+        }
+    }
+
+    pub fn render<B: Backend>(&self, f: &mut Frame<B>, area: Rect, block: Block) {
+        let inner = block.inner(area);
+        f.render_widget(block, area);
+        if self.items.is_empty() {
+            f.render_widget(
+                Paragraph::new("No news").style(Style::default().fg(Color::White).bg(Color::Black)),
+                inner,
+            );
+            return;
+        }
+        // A page of headlines with a header and borders, and the story underneath
+        let list_and_detail = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(PAGE as u16 + 3), Constraint::Min(0)].as_ref())
+            .split(inner);
+
+        let page = self.selected / PAGE;
+        let first = page * PAGE;
+        let rows = self.items[first..(first + PAGE).min(self.items.len())]
+            .iter()
+            .map(|item| {
+                let marker = if self.is_new(item) { "NEW" } else { "" };
+                Row::new(vec![
+                    marker.to_string(),
+                    Utc.timestamp(item.datetime, 0)
+                        .format("%m-%d %H:%M")
+                        .to_string(),
+                    item.source.clone(),
+                    item.headline.clone(),
+                ])
+                .style(if self.is_new(item) {
+                    Style::default().fg(Color::Yellow)
+                } else {
+                    Style::default()
+                })
+            });
+        let table = Table::new(rows)
+            .header(
+                Row::new(vec!["", "Date", "Source", "Headline"])
+                    .style(Style::default().add_modifier(Modifier::BOLD)),
+            )
+            .block(
+                Block::default()
+                    .title(format!(
+                        "Page {} of {}, {} stories",
+                        page + 1,
+                        self.pages(),
+                        self.items.len()
+                    ))
+                    .borders(Borders::ALL),
+            )
+            .style(Style::default().fg(Color::White).bg(Color::Black))
+            .widths(&[
+                Constraint::Length(3),
+                Constraint::Length(11),
+                Constraint::Length(14),
+                Constraint::Percentage(100),
+            ])
+            .column_spacing(1)
+            .highlight_style(Style::default().bg(Color::DarkGray))
+            .highlight_symbol(">>");
+        let mut table_state = TableState::default();
+        table_state.select(Some(self.selected - first));
+        f.render_stateful_widget(table, list_and_detail[0], &mut table_state);
+
+        let item = &self.items[self.selected];
+        let summary = if item.summary.is_empty() {
+            "No summary"
+        } else {
+            &item.summary
+        };
+        let detail = vec![
+            Spans::from(Span::styled(
+                item.headline.clone(),
+                Style::default().add_modifier(Modifier::BOLD),
+            )),
+            Spans::from(Span::styled(
+                format!(
+                    "{} | {}",
+                    item.source,
+                    Utc.timestamp(item.datetime, 0).format("%Y-%m-%d %H:%M UTC")
+                ),
+                Style::default().fg(Color::Gray),
+            )),
+            Spans::from(""),
+            Spans::from(summary.to_string()),
+            Spans::from(""),
+            Spans::from(Span::styled(
+                item.url.clone(),
+                Style::default().fg(Color::Cyan),
+            )),
+        ];
+        let detail = Paragraph::new(detail)
+            .block(
+                Block::default()
+                    .title(format!(
+                        "Story {} of {}",
+                        self.selected + 1,
+                        self.items.len()
+                    ))
+                    .borders(Borders::ALL),
+            )
+            .style(Style::default().fg(Color::White).bg(Color::Black))
+            .wrap(Wrap { trim: false });
+        f.render_widget(detail, list_and_detail[1]);
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct NewsFeed {
+    // Everything fetched since the tool started
+    pub list: NewsList,
+    // The newest id so far, sent as minId so Finnhub only sends what came after it
+    pub min_id: i64,
+    // The newest id when the category was last opened. Anything after it is marked new,
+    // however many refreshes it took to come in.
+    pub last_seen: Option<i64>,
+}
+
+impl NewsFeed {
+    /// Adds the items that weren't there yet. The first fetch has no earlier look to
+    /// compare with, so everything in it counts as seen.
+    pub fn merge(&mut self, items: Vec<MarketNews>) {
+        let previous = self.min_id;
+        let fresh = items
+            .into_iter()
+            .filter(|item| item.id > previous)
+            .map(NewsItem::from)
+            .collect::<Vec<_>>();
+        self.min_id = fresh.iter().map(|item| item.id).max().unwrap_or(previous);
+        let last_seen = *self.last_seen.get_or_insert(self.min_id);
+        self.list.new_after = Some(last_seen);
+        self.list.items.extend(fresh);
+        self.list.sort();
+        self.list.selected = 0;
+    }
+
+    /// Everything fetched so far has been looked at, so only what comes after it is new
+    pub fn seen(&mut self) {
+        self.last_seen = Some(self.min_id);
+        self.list.new_after = self.last_seen;
+    }
+}
+
+/// Kept in State so the feeds last between looks and switching category
+#[derive(Debug, Default)]
+pub struct MarketNewsFeeds {
+    pub category: NewsCategory,
+    pub feeds: HashMap<NewsCategory, NewsFeed>,
+}
+
+impl MarketNewsFeeds {
+    pub fn min_id(&self, category: NewsCategory) -> i64 {
+        self.feeds.get(&category).map_or(0, |feed| feed.min_id)
+    }
+
+    pub fn merge(&mut self, category: NewsCategory, items: Vec<MarketNews>) {
+        self.feeds.entry(category).or_default().merge(items);
+    }
+
+    /// Shows a category, and what was in it the last time around stops being new
+    pub fn open(&mut self, category: NewsCategory) {
+        self.category = category;
+        if let Some(feed) = self.feeds.get_mut(&category) {
+            feed.seen();
+        }
+    }
+
+    pub fn current(&self) -> Option<&NewsList> {
+        self.feeds.get(&self.category).map(|feed| &feed.list)
+    }
+
+    /// None until the category has been fetched
+    pub fn current_mut(&mut self) -> Option<&mut NewsList> {
+        self.feeds
+            .get_mut(&self.category)
+            .map(|feed| &mut feed.list)
+    }
+
+    pub fn render<B: Backend>(&self, f: &mut Frame<B>, area: Rect, block: Block) {
+        match self.current() {
+            Some(list) => list.render(f, area, block),
+            None => f.render_widget(
+                Paragraph::new(format!("Getting {} news...", self.category))
+                    .block(block)
+                    .style(Style::default().fg(Color::White).bg(Color::Black)),
+                area,
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn story(id: i64) -> MarketNews {
+        MarketNews {
+            category: "general".to_string(),
+            datetime: id * 60,
+            headline: format!("Story {id}"),
+            id,
+            source: "CNBC".to_string(),
+            summary: String::new(),
+            url: String::new(),
+        }
+    }
+
+    fn ids(list: &NewsList) -> Vec<i64> {
+        list.items.iter().map(|item| item.id).collect()
+    }
+
+    #[test]
+    fn first_merge_marks_nothing_new() {
+        let mut feed = NewsFeed::default();
+        feed.merge(vec![story(1), story(3), story(2)]);
+        assert_eq!(ids(&feed.list), [3, 2, 1]);
+        assert_eq!(feed.min_id, 3);
+        assert_eq!(feed.list.new_count(), 0);
+    }
+
+    #[test]
+    fn later_merge_adds_and_marks_only_newer_stories() {
+        let mut feed = NewsFeed::default();
+        feed.merge(vec![story(1), story(2)]);
+        feed.list.selected = 1;
+        // Finnhub can send back what it already sent
+        feed.merge(vec![story(2), story(4), story(3)]);
+        assert_eq!(ids(&feed.list), [4, 3, 2, 1]);
+        assert_eq!(feed.min_id, 4);
+        assert_eq!(feed.list.new_count(), 2);
+        assert_eq!(feed.list.selected, 0);
+    }
+
+    #[test]
+    fn new_stories_stay_new_until_the_category_is_opened_again() {
+        let mut feed = NewsFeed::default();
+        feed.merge(vec![story(1)]);
+        feed.merge(vec![story(2)]);
+        feed.merge(vec![story(3)]);
+        // Nothing came in, which doesn't make 2 and 3 any less new
+        feed.merge(Vec::new());
+        assert_eq!(feed.min_id, 3);
+        assert_eq!(feed.list.new_count(), 2);
+        feed.seen();
+        assert_eq!(feed.list.new_count(), 0);
+        feed.merge(vec![story(4)]);
+        assert_eq!(feed.list.new_count(), 1);
+        assert_eq!(feed.list.items.len(), 4);
+    }
+
+    #[test]
+    fn feeds_are_kept_per_category() {
+        let mut feeds = MarketNewsFeeds::default();
+        feeds.merge(NewsCategory::Crypto, vec![story(5)]);
+        assert_eq!(feeds.min_id(NewsCategory::Crypto), 5);
+        assert_eq!(feeds.min_id(NewsCategory::General), 0);
+        assert!(feeds.current().is_none());
+    }
+
+    #[test]
+    fn opening_a_category_only_marks_that_one_seen() {
+        let mut feeds = MarketNewsFeeds::default();
+        for category in [NewsCategory::General, NewsCategory::Crypto] {
+            feeds.merge(category, vec![story(1)]);
+            feeds.merge(category, vec![story(2)]);
+        }
+        feeds.open(NewsCategory::Crypto);
+        assert_eq!(feeds.current().unwrap().new_count(), 0);
+        assert_eq!(feeds.feeds[&NewsCategory::General].list.new_count(), 1);
+        feeds.open(NewsCategory::General);
+        assert_eq!(feeds.current().unwrap().new_count(), 0);
+        assert_eq!(feeds.feeds[&NewsCategory::General].last_seen, Some(2));
+    }
+}
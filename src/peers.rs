@@ -0,0 +1,157 @@
+//! Company peers for the Results window: the companies Finnhub puts in the same industry,
+//! named from the market's symbol list so they're more than tickers.
+
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Row, Table, TableState},
+    Frame,
+};
+
+#[derive(Debug, Clone)]
+pub struct Peer {
+    pub symbol: String,
+    // None until it's found in the symbol list
+    pub description: Option<String>,
+}
+
+/// Kept in State so the selection survives redraws
+#[derive(Debug, Clone)]
+pub struct PeerList {
+    pub symbol: String,
+    pub peers: Vec<Peer>,
+    pub selected: usize,
+}
+
+impl PeerList {
+    /// Finnhub lists the company itself among its peers, which isn't much use here
+    pub fn new(symbol: String, peers: Vec<String>) -> Self {
+        let peers = peers
+            .into_iter()
+            .filter(|peer| *peer != symbol)
+            .map(|symbol| Peer {
+                symbol,
+                description: None,
+            })
+            .collect();
+        Self {
+            symbol,
+            peers,
+            selected: 0,
+        }
+    }
+
+    /// Finds each peer in State::companies, which is "DESCRIPTION : SYMBOL" for the current market
+    pub fn describe(&mut self, companies: &[String]) {
+        for peer in &mut self.peers {
+            let suffix = format!(" : {}", peer.symbol);
+            peer.description = companies
+                .iter()
+                .find_map(|company| company.strip_suffix(&suffix))
+                .map(str::to_string);
+        }
+    }
+
+    pub fn selected_symbol(&self) -> Option<&str> {
+        self.peers
+            .get(self.selected)
+            .map(|peer| peer.symbol.as_str())
+    }
+
+    pub fn up(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn down(&mut self) {
+        if self.selected + 1 < self.peers.len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn render<B: Backend>(&self, f: &mut Frame<B>, area: Rect, block: Block) {
+        let rows = self.peers.iter().map(|peer| {
+            Row::new(vec![
+                peer.symbol.clone(),
+                peer.description
+                    .clone()
+                    .unwrap_or_else(|| "Not in this market's symbol list".to_string()),
+            ])
+        });
+        let table = Table::new(rows)
+            .header(
+                Row::new(vec!["Symbol", "Description"])
+                    .style(Style::default().add_modifier(Modifier::BOLD)),
+            )
+            .block(block)
+            .style(Style::default().fg(Color::White).bg(Color::Black))
+            .widths(&[Constraint::Length(10), Constraint::Percentage(85)])
+            .column_spacing(1)
+            .highlight_style(Style::default().bg(Color::Gray))
+            .highlight_symbol(">>");
+        let mut table_state = TableState::default();
+        if !self.peers.is_empty() {
+            table_state.select(Some(self.selected));
+        }
+        f.render_stateful_widget(table, area, &mut table_state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::provider::{FixtureProvider, MarketDataProvider};
+
+    fn list() -> PeerList {
+        let fixtures = FixtureProvider::new(Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures"));
+        PeerList::new("AAPL".to_string(), fixtures.peers("AAPL").unwrap())
+    }
+
+    #[test]
+    fn the_company_itself_is_left_out() {
+        let list = list();
+        let symbols = list
+            .peers
+            .iter()
+            .map(|peer| peer.symbol.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            symbols,
+            ["MSFT", "GOOGL", "META", "NVDA", "IBM", "DELL", "HPQ"]
+        );
+        assert_eq!(list.selected_symbol(), Some("MSFT"));
+    }
+
+    #[test]
+    fn peers_are_named_from_the_symbol_list() {
+        let mut list = list();
+        list.describe(&[
+            "MICROSOFT CORP : MSFT".to_string(),
+            "INTL BUSINESS MACHINES CORP : IBM".to_string(),
+            "MSFT HOLDINGS : MSFTX".to_string(),
+        ]);
+        assert_eq!(list.peers[0].description.as_deref(), Some("MICROSOFT CORP"));
+        assert_eq!(
+            list.peers[4].description.as_deref(),
+            Some("INTL BUSINESS MACHINES CORP")
+        );
+        assert_eq!(list.peers[1].description, None);
+    }
+
+    #[test]
+    fn selection_stays_inside_the_list() {
+        let mut list = list();
+        list.up();
+        assert_eq!(list.selected, 0);
+        for _ in 0..10 {
+            list.down();
+        }
+        assert_eq!(list.selected_symbol(), Some("HPQ"));
+        assert_eq!(
+            PeerList::new("AAPL".to_string(), vec![]).selected_symbol(),
+            None
+        );
+    }
+}
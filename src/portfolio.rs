@@ -0,0 +1,669 @@
+//! Positions the user holds, kept as buy and sell transactions and priced with the
+//! same quotes as the Quote choice.
+
+use std::{collections::HashMap, path::PathBuf};
+
+use anyhow::{Context, Error};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Cell, Row, Table, TableState},
+    Frame,
+};
+
+use crate::{
+    api::{ForexRates, Quote},
+    config::data_dir,
+    lots::{match_lots, Lot, LotMethod, LotReport, Side, Transaction},
+};
+
+/// What portfolio.json held before there were sells: one buy per position
+#[derive(Debug, Deserialize)]
+struct Holding {
+    symbol: String,
+    quantity: f64,
+    cost_basis: f64,
+    date: NaiveDate,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Ledger {
+    #[serde(default)]
+    method: LotMethod,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    base_currency: Option<String>,
+    transactions: Vec<Transaction>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum SavedPortfolio {
+    Ledger(Ledger),
+    Holdings(Vec<Holding>),
+}
+
+/// A lot with its latest quote worked in, all in the base currency if there is one
+#[derive(Debug, Clone)]
+pub struct Valuation {
+    pub cost: f64,
+    pub market_value: f64,
+    pub unrealized: f64,
+    pub unrealized_percent: f64,
+    pub day_change: f64,
+}
+
+impl Valuation {
+    pub fn new(cost: f64, market_value: f64, day_change: f64) -> Self {
+        let unrealized = market_value - cost;
+        let unrealized_percent = if cost == 0.0 {
+            0.0
+        } else {
+            unrealized / cost * 100.0
+        };
+        Self {
+            cost,
+            market_value,
+            unrealized,
+            unrealized_percent,
+            day_change,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Portfolio {
+    pub transactions: Vec<Transaction>,
+    pub method: LotMethod,
+    // Open lots and realized gains worked out from the transactions
+    pub report: LotReport,
+    // Latest quote for each symbol, or why there isn't one
+    pub quotes: HashMap<String, Result<Quote, String>>,
+    // Index into report.open
+    pub selected: usize,
+    // Values are shown in this currency when it's set, otherwise in whatever each symbol trades in
+    pub base_currency: Option<String>,
+    // Rates against base_currency, or why there aren't any
+    pub rates: Option<Result<ForexRates, String>>,
+    // What each symbol is quoted in
+    pub currencies: HashMap<String, String>,
+    // None when there's nowhere to save it, in which case it only lasts until the tool closes
+    path: Option<PathBuf>,
+}
+
+impl Portfolio {
+    /// Reads portfolio.json from the data directory, which is fine to be missing
+    pub fn load() -> Result<Self, Error> {
+        let path = data_dir().map(|dir| dir.join("portfolio.json"));
+        let ledger = match &path {
+            Some(path) if path.exists() => {
+                let text = std::fs::read_to_string(path)
+                    .with_context(|| format!("Couldn't read portfolio {}", path.display()))?;
+                let saved = serde_json::from_str(&text)
+                    .with_context(|| format!("Couldn't understand portfolio {}", path.display()))?;
+                match saved {
+                    SavedPortfolio::Ledger(ledger) => ledger,
+                    SavedPortfolio::Holdings(holdings) => Ledger {
+                        method: LotMethod::default(),
+                        base_currency: None,
+                        transactions: holdings
+                            .into_iter()
+                            .zip(1..)
+                            .map(|(holding, id)| Transaction {
+                                id,
+                                date: holding.date,
+                                side: Side::Buy,
+                                symbol: holding.symbol,
+                                quantity: holding.quantity,
+                                price: holding.cost_basis,
+                                fees: 0.0,
+                                lot: None,
+                                currency: None,
+                            })
+                            .collect(),
+                    },
+                }
+            }
+            _ => Ledger {
+                method: LotMethod::default(),
+                base_currency: None,
+                transactions: Vec::new(),
+            },
+        };
+        let report = match_lots(&ledger.transactions, ledger.method)?;
+        Ok(Self {
+            transactions: ledger.transactions,
+            method: ledger.method,
+            base_currency: ledger.base_currency,
+            report,
+            path,
+            ..Default::default()
+        })
+    }
+
+    fn save(&self) -> Result<(), Error> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("Couldn't make directory {}", dir.display()))?;
+        }
+        let ledger = Ledger {
+            method: self.method,
+            base_currency: self.base_currency.clone(),
+            transactions: self.transactions.clone(),
+        };
+        let json = serde_json::to_string_pretty(&ledger)?;
+        std::fs::write(path, json)
+            .with_context(|| format!("Couldn't save portfolio to {}", path.display()))
+    }
+
+    /// The id for the next transaction, which is also the lot number if it's a buy
+    pub fn next_id(&self) -> u64 {
+        self.transactions
+            .iter()
+            .map(|transaction| transaction.id)
+            .max()
+            .unwrap_or(0)
+            + 1
+    }
+
+    /// Works the lots out again, leaving everything as it was if the transactions don't add up
+    fn rematch(&mut self, transactions: Vec<Transaction>, method: LotMethod) -> Result<(), Error> {
+        self.report = match_lots(&transactions, method)?;
+        self.transactions = transactions;
+        self.method = method;
+        self.selected = self.selected.min(self.report.open.len().saturating_sub(1));
+        self.save()
+    }
+
+    pub fn add(&mut self, transaction: Transaction) -> Result<(), Error> {
+        let side = transaction.side;
+        let id = transaction.id;
+        let mut transactions = self.transactions.clone();
+        transactions.push(transaction);
+        self.rematch(transactions, self.method)?;
+        if side == Side::Buy {
+            if let Some(index) = self.report.open.iter().position(|lot| lot.id == id) {
+                self.selected = index;
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds them all or, if they don't add up with what's there, none of them
+    pub fn add_all(&mut self, new_transactions: Vec<Transaction>) -> Result<(), Error> {
+        let mut transactions = self.transactions.clone();
+        transactions.extend(new_transactions);
+        self.rematch(transactions, self.method)
+    }
+
+    /// Deletes the buy behind the selected lot, as long as nothing has been sold out of it
+    pub fn remove_selected(&mut self) -> Result<(), Error> {
+        let Some(lot) = self.report.open.get(self.selected) else {
+            return Ok(());
+        };
+        if self.report.realized.iter().any(|gain| gain.lot == lot.id) {
+            return Err(anyhow::anyhow!(format!(
+                "Lot {} has been sold from, undo the sells first (u)",
+                lot.id
+            )));
+        }
+        let id = lot.id;
+        let transactions = self
+            .transactions
+            .iter()
+            .filter(|transaction| transaction.id != id)
+            .cloned()
+            .collect();
+        self.rematch(transactions, self.method)
+    }
+
+    /// Takes back the last transaction typed in, buy or sell
+    pub fn undo(&mut self) -> Result<(), Error> {
+        let mut transactions = self.transactions.clone();
+        let Some(last) = transactions
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, transaction)| transaction.id)
+            .map(|(index, _)| index)
+        else {
+            return Ok(());
+        };
+        transactions.remove(last);
+        self.rematch(transactions, self.method)
+    }
+
+    /// FIFO -> LIFO -> specific ID. Past sells get matched again with the new method.
+    pub fn next_method(&mut self) -> Result<(), Error> {
+        let method = self.method.next();
+        self.rematch(self.transactions.clone(), method)
+            .map_err(|e| anyhow::anyhow!(format!("Can't switch to {method}: {e}")))
+    }
+
+    /// Writes the realized gains to realized_gains.csv in the data directory
+    pub fn export_gains(&self) -> Result<PathBuf, Error> {
+        let path = data_dir()
+            .map(|dir| dir.join("realized_gains.csv"))
+            .ok_or_else(|| anyhow::anyhow!("No data directory to export to, set HOME"))?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("Couldn't make directory {}", dir.display()))?;
+        }
+        std::fs::write(&path, self.report.to_csv())
+            .with_context(|| format!("Couldn't export realized gains to {}", path.display()))?;
+        Ok(path)
+    }
+
+    /// "base EUR" or "base" on its own, but not a transaction for a ticker called BASE,
+    /// which always has more words after it
+    pub fn base_command(input: &str) -> Option<Option<&str>> {
+        match input.split_whitespace().collect::<Vec<_>>().as_slice() {
+            [word] if word.eq_ignore_ascii_case("base") => Some(None),
+            [word, code]
+                if word.eq_ignore_ascii_case("base")
+                    && code.len() == 3
+                    && code.chars().all(|c| c.is_ascii_alphabetic()) =>
+            {
+                Some(Some(code))
+            }
+            _ => None,
+        }
+    }
+
+    /// "base EUR" shows values in euros and "base" on its own in each symbol's own currency
+    pub fn set_base_currency(&mut self, currency: Option<&str>) -> Result<(), Error> {
+        let currency = match currency {
+            Some(code) if code.len() == 3 && code.chars().all(|c| c.is_ascii_alphabetic()) => {
+                Some(code.to_uppercase())
+            }
+            Some(code) => {
+                return Err(anyhow::anyhow!(format!(
+                    "{code} isn't a currency code, type e.g. base EUR"
+                )))
+            }
+            None => None,
+        };
+        if currency != self.base_currency {
+            self.rates = None;
+        }
+        self.base_currency = currency;
+        self.save()
+    }
+
+    /// For the Results title, e.g. ", in EUR, 2 lots unpriced"
+    pub fn status_label(&self) -> String {
+        match self.unpriced() {
+            0 => self.base_label(),
+            1 => format!("{}, 1 lot unpriced", self.base_label()),
+            unpriced => format!("{}, {unpriced} lots unpriced", self.base_label()),
+        }
+    }
+
+    fn base_label(&self) -> String {
+        match (&self.base_currency, &self.rates) {
+            (None, _) if self.total_currency().is_none() => {
+                ", mixed currencies (type base EUR to total them in euros)".to_string()
+            }
+            (None, _) => String::new(),
+            (Some(base), Some(Err(e))) => format!(", in {base} (no rates: {e})"),
+            (Some(base), _) => format!(", in {base}"),
+        }
+    }
+
+    /// What a symbol is quoted in, if it's known yet
+    fn quoted_in(&self, symbol: &str) -> Option<&str> {
+        self.currencies.get(symbol).map(String::as_str)
+    }
+
+    pub fn up(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn down(&mut self) {
+        if self.selected + 1 < self.report.open.len() {
+            self.selected += 1;
+        }
+    }
+
+    /// Each symbol still held once, even if it's held in a few lots
+    pub fn symbols(&self) -> Vec<String> {
+        let mut symbols = self
+            .report
+            .open
+            .iter()
+            .map(|lot| lot.symbol.clone())
+            .collect::<Vec<_>>();
+        symbols.sort();
+        symbols.dedup();
+        symbols
+    }
+
+    pub fn update_quotes(&mut self, quotes: Vec<(String, Result<Quote, String>)>) {
+        self.quotes.extend(quotes);
+    }
+
+    /// The lot priced with its quote, or what to show instead: "..." while waiting, "n/a"
+    /// without a quote, "no rate" when a currency can't be converted to the base currency
+    /// and "mixed" when it was paid in one currency and trades in another with no base
+    /// currency to bring them together
+    pub fn valuation(&self, lot: &Lot) -> Result<Valuation, &'static str> {
+        let quote = match self.quotes.get(&lot.symbol) {
+            Some(Ok(quote)) => quote,
+            Some(Err(_)) => return Err("n/a"),
+            None => return Err("..."),
+        };
+        let quoted_in = self.quoted_in(&lot.symbol);
+        let (cost, market_value, day_change) = (
+            lot.cost(),
+            lot.quantity * quote.c,
+            lot.quantity * quote.change(),
+        );
+        if self.base_currency.is_none() {
+            // A buy with no currency of its own was paid in whatever the symbol trades in
+            return match (lot.currency.as_deref(), quoted_in) {
+                (None, _) => Ok(Valuation::new(cost, market_value, day_change)),
+                (Some(paid_in), Some(quoted_in)) if paid_in.eq_ignore_ascii_case(quoted_in) => {
+                    Ok(Valuation::new(cost, market_value, day_change))
+                }
+                (Some(_), Some(_)) => Err("mixed"),
+                // Its profile didn't say what it trades in
+                (Some(_), None) => Err("n/a"),
+            };
+        }
+        let rates = match &self.rates {
+            Some(Ok(rates)) => rates,
+            Some(Err(_)) => return Err("no rate"),
+            None => return Err("..."),
+        };
+        let quoted_in = quoted_in.ok_or("no rate")?;
+        let paid_in = lot.currency.as_deref().unwrap_or(quoted_in);
+        // Costs are converted at today's rate too, so P&L includes any move in the currency
+        let in_base = |amount: f64, currency: &str| {
+            rates
+                .to_base(amount, &currency.to_uppercase())
+                .ok_or("no rate")
+        };
+        Ok(Valuation::new(
+            in_base(cost, paid_in)?,
+            in_base(market_value, quoted_in)?,
+            in_base(day_change, quoted_in)?,
+        ))
+    }
+
+    /// What the values in the Total row are in. None when they'd be in different
+    /// currencies, which only happens without a base currency.
+    fn total_currency(&self) -> Option<Option<&str>> {
+        if let Some(base) = &self.base_currency {
+            return Some(Some(base));
+        }
+        let mut currencies = self
+            .report
+            .open
+            .iter()
+            .filter(|lot| self.valuation(lot).is_ok())
+            .map(|lot| self.quoted_in(&lot.symbol));
+        let first = currencies.next().flatten();
+        currencies
+            .all(|currency| currency == first)
+            .then_some(first)
+    }
+
+    /// Open lots left out of the totals because they don't have a value
+    pub fn unpriced(&self) -> usize {
+        self.report
+            .open
+            .iter()
+            .filter(|lot| self.valuation(lot).is_err())
+            .count()
+    }
+
+    /// Market value of every open lot that has a price
+    pub fn total_value(&self) -> f64 {
+        self.report
+            .open
+            .iter()
+            .filter_map(|lot| self.valuation(lot).ok())
+            .map(|valuation| valuation.market_value)
+            .sum()
+    }
+
+    /// A symbol's share of the portfolio as a percentage, with all of its lots added up.
+    /// None without one currency for everything or while some lots are unpriced, since a
+    /// weight out of that total wouldn't mean anything.
+    pub fn weight(&self, symbol: &str) -> Option<f64> {
+        if self.total_currency().is_none() || self.unpriced() > 0 {
+            return None;
+        }
+        let total_value = self.total_value();
+        if total_value == 0.0 {
+            return Some(0.0);
+        }
+        let value = self
+            .report
+            .open
+            .iter()
+            .filter(|lot| lot.symbol == symbol)
+            .filter_map(|lot| self.valuation(lot).ok())
+            .map(|valuation| valuation.market_value)
+            .sum::<f64>();
+        Some(value / total_value * 100.0)
+    }
+
+    pub fn render<B: Backend>(&self, f: &mut Frame<B>, area: Rect, block: Block) {
+        let total_value = self.total_value();
+        // Without one currency for everything, the totals don't mean anything
+        let mixed = self.total_currency().is_none();
+        let partial = self.unpriced() > 0;
+        let colour = |number: f64| {
+            Style::default().fg(if number >= 0.0 {
+                Color::Green
+            } else {
+                Color::Red
+            })
+        };
+
+        let mut rows =
+            self.report
+                .open
+                .iter()
+                .map(|lot| {
+                    let mut cells = vec![
+                        Cell::from(lot.id.to_string()),
+                        Cell::from(lot.symbol.clone()),
+                        Cell::from(format!("{}", lot.quantity)),
+                        Cell::from(format!("{:.2}", lot.cost_basis)),
+                    ];
+                    match (self.valuation(lot), self.quotes.get(&lot.symbol)) {
+                        (Ok(valuation), Some(Ok(quote))) => {
+                            // The same on each of a symbol's lots, since it's their weight together
+                            let weight = self
+                                .weight(&lot.symbol)
+                                .map_or_else(|| "-".to_string(), |weight| format!("{weight:.1}%"));
+                            cells.extend([
+                                Cell::from(format!("{:.2}", quote.c)),
+                                Cell::from(format!("{:.2}", valuation.market_value)),
+                                Cell::from(format!("{:+.2}", valuation.unrealized))
+                                    .style(colour(valuation.unrealized)),
+                                Cell::from(format!("{:+.1}%", valuation.unrealized_percent))
+                                    .style(colour(valuation.unrealized)),
+                                Cell::from(format!("{:+.2}", valuation.day_change))
+                                    .style(colour(valuation.day_change)),
+                                Cell::from(weight),
+                            ]);
+                        }
+                        (Err(reason), Some(Ok(quote))) => cells
+                            .extend([Cell::from(format!("{:.2}", quote.c)), Cell::from(reason)]),
+                        (valuation, _) => cells.push(Cell::from(valuation.err().unwrap_or("..."))),
+                    }
+                    Row::new(cells)
+                })
+                .collect::<Vec<_>>();
+
+        let (cost, unrealized, day_change) = self
+            .report
+            .open
+            .iter()
+            .filter_map(|lot| self.valuation(lot).ok())
+            .fold(
+                (0.0, 0.0, 0.0),
+                |(cost, unrealized, day_change), valuation| {
+                    (
+                        cost + valuation.cost,
+                        unrealized + valuation.unrealized,
+                        day_change + valuation.day_change,
+                    )
+                },
+            );
+        let unrealized_percent = if cost == 0.0 {
+            0.0
+        } else {
+            unrealized / cost * 100.0
+        };
+        let total = if mixed {
+            // Adding euros to dollars would just be a number
+            vec![
+                Cell::from(""),
+                Cell::from("Total"),
+                Cell::from(""),
+                Cell::from("mixed"),
+                Cell::from(""),
+                Cell::from("mixed"),
+                Cell::from("mixed"),
+                Cell::from(""),
+                Cell::from("mixed"),
+                Cell::from(""),
+            ]
+        } else {
+            vec![
+                Cell::from(""),
+                Cell::from("Total"),
+                Cell::from(""),
+                Cell::from(format!("{cost:.2}")),
+                Cell::from(""),
+                Cell::from(format!("{total_value:.2}")),
+                Cell::from(format!("{unrealized:+.2}")).style(colour(unrealized)),
+                Cell::from(format!("{unrealized_percent:+.1}%")).style(colour(unrealized)),
+                Cell::from(format!("{day_change:+.2}")).style(colour(day_change)),
+                Cell::from(if partial { "partial" } else { "100%" }),
+            ]
+        };
+        rows.push(Row::new(total).style(Style::default().add_modifier(Modifier::BOLD)));
+
+        let table = Table::new(rows)
+            .header(
+                Row::new(vec![
+                    "Lot", "Symbol", "Qty", "Cost", "Price", "Value", "P&L", "P&L %", "Day",
+                    "Weight",
+                ])
+                .style(Style::default().add_modifier(Modifier::BOLD)),
+            )
+            .block(block)
+            .style(Style::default().fg(Color::White).bg(Color::Black))
+            // Percentages that leave room for the spacing, so every column stays on screen when the Results window is narrow
+            .widths(&[
+                Constraint::Percentage(5),
+                Constraint::Percentage(8),
+                Constraint::Percentage(6),
+                Constraint::Percentage(9),
+                Constraint::Percentage(9),
+                Constraint::Percentage(10),
+                Constraint::Percentage(11),
+                Constraint::Percentage(8),
+                Constraint::Percentage(9),
+                Constraint::Percentage(9),
+            ])
+            .column_spacing(1)
+            .highlight_style(Style::default().bg(Color::Gray))
+            .highlight_symbol(">>");
+
+        let mut table_state = TableState::default();
+        if !self.report.open.is_empty() {
+            table_state.select(Some(self.selected));
+        }
+        f.render_stateful_widget(table, area, &mut table_state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn buy(id: u64, symbol: &str, quantity: f64) -> Transaction {
+        Transaction {
+            id,
+            date: NaiveDate::from_ymd_opt(2023, 1, 1).unwrap(),
+            side: Side::Buy,
+            symbol: symbol.to_string(),
+            quantity,
+            price: 100.0,
+            fees: 0.0,
+            lot: None,
+            currency: None,
+        }
+    }
+
+    fn quote(price: f64) -> Result<Quote, String> {
+        Ok(Quote {
+            c: price,
+            h: price,
+            l: price,
+            o: price,
+            pc: price,
+            t: 0,
+        })
+    }
+
+    fn portfolio() -> Portfolio {
+        let mut portfolio = Portfolio::default();
+        portfolio
+            .add_all(vec![
+                buy(1, "AAPL", 10.0),
+                buy(2, "MSFT", 5.0),
+                buy(3, "AAPL", 10.0),
+            ])
+            .unwrap();
+        portfolio.update_quotes(vec![
+            ("AAPL".to_string(), quote(150.0)),
+            ("MSFT".to_string(), quote(600.0)),
+        ]);
+        portfolio
+    }
+
+    #[test]
+    fn weight_adds_up_a_symbols_lots() {
+        let portfolio = portfolio();
+        assert_eq!(portfolio.report.open.len(), 3);
+        // 3000 of AAPL across two lots and 3000 of MSFT in one
+        assert_eq!(portfolio.weight("AAPL"), Some(50.0));
+        assert_eq!(portfolio.weight("MSFT"), Some(50.0));
+        assert_eq!(portfolio.weight("TSLA"), Some(0.0));
+    }
+
+    #[test]
+    fn weight_needs_every_lot_priced_in_one_currency() {
+        let mut portfolio = portfolio();
+        portfolio.quotes.remove("MSFT");
+        assert_eq!(portfolio.weight("AAPL"), None);
+
+        let mut portfolio = self::portfolio();
+        portfolio.currencies.extend([
+            ("AAPL".to_string(), "USD".to_string()),
+            ("MSFT".to_string(), "EUR".to_string()),
+        ]);
+        assert_eq!(portfolio.weight("AAPL"), None);
+    }
+
+    #[test]
+    fn base_command_only_takes_base_and_a_code() {
+        assert_eq!(Portfolio::base_command("base"), Some(None));
+        assert_eq!(Portfolio::base_command(" Base eur "), Some(Some("eur")));
+        // A ticker called BASE is a transaction
+        assert_eq!(Portfolio::base_command("BASE 10 20"), None);
+        assert_eq!(Portfolio::base_command("base EURO"), None);
+        assert_eq!(Portfolio::base_command("buy AAPL 5 180"), None);
+    }
+}
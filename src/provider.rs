@@ -0,0 +1,490 @@
+//! Where market data comes from. FinanceClient only ever talks to a MarketDataProvider,
+//! so the TUI can run against Finnhub or against JSON files on disk.
+
+use std::{
+    fmt::Debug,
+    path::{Path, PathBuf},
+    sync::mpsc::SyncSender,
+    time::Duration,
+};
+
+use anyhow::{Context, Error};
+use chrono::NaiveDate;
+use serde::de::DeserializeOwned;
+
+use crate::{
+    api::{
+        BasicFinancials, Candles, CompanyNews, CompanyProfile, CryptoSymbol, EarningsCalendar,
+        EarningsSurprise, FinancialsReported, ForexRates, ForexSymbol, InsiderSentimentData,
+        InsiderTransactions, IpoCalendar, MarketNews, Quote, RecommendationTrend, Resolution,
+        SecFiling, StockSymbol,
+    },
+    app::Command,
+    financials::Period,
+    limiter::{backoff, retry_after, ApiStatus, RateLimiter, MAX_RETRIES},
+    FINNHUB_URL,
+};
+
+/// Anything that can answer Finnhub-shaped requests. Only get_text needs implementing,
+/// but a provider for another data source can override the typed methods instead.
+pub trait MarketDataProvider: Send + Debug {
+    /// The raw JSON for an endpoint like /stock/profile2 with its query parameters
+    fn get_text(&self, endpoint: &str, query: &[(&str, String)]) -> Result<String, Error>;
+
+    /// How old the oldest cached reply handed out since the last call is.
+    /// None when everything came fresh from the source.
+    fn take_cache_age(&self) -> Option<Duration> {
+        None
+    }
+
+    /// /stock/profile2?symbol=AAPL
+    fn company_profile(&self, symbol: &str) -> Result<CompanyProfile, Error> {
+        request(self, "/stock/profile2", &[("symbol", symbol.to_string())])
+    }
+
+    /// /stock/symbol?exchange=US
+    fn stock_symbols(&self, exchange: &str) -> Result<Vec<StockSymbol>, Error> {
+        request(self, "/stock/symbol", &[("exchange", exchange.to_string())])
+    }
+
+    /// /company-news?symbol=AAPL&from=2021-09-01&to=2021-09-09
+    fn company_news(
+        &self,
+        symbol: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<CompanyNews>, Error> {
+        request(
+            self,
+            "/company-news",
+            &[
+                ("symbol", symbol.to_string()),
+                ("from", from.to_string()),
+                ("to", to.to_string()),
+            ],
+        )
+    }
+
+    /// /news?category=general&minId=0
+    fn market_news(&self, category: &str, min_id: i64) -> Result<Vec<MarketNews>, Error> {
+        request(
+            self,
+            "/news",
+            &[
+                ("category", category.to_string()),
+                ("minId", min_id.to_string()),
+            ],
+        )
+    }
+
+    /// /quote?symbol=AAPL
+    fn quote(&self, symbol: &str) -> Result<Quote, Error> {
+        request(self, "/quote", &[("symbol", symbol.to_string())])
+    }
+
+    /// /stock/candle?symbol=AAPL&resolution=D&from=1572651390&to=1575243390
+    fn candles(
+        &self,
+        symbol: &str,
+        resolution: Resolution,
+        from: i64,
+        to: i64,
+    ) -> Result<Candles, Error> {
+        request(
+            self,
+            "/stock/candle",
+            &[
+                ("symbol", symbol.to_string()),
+                ("resolution", resolution.to_string()),
+                ("from", from.to_string()),
+                ("to", to.to_string()),
+            ],
+        )
+    }
+
+    /// /stock/insider-sentiment?symbol=TSLA&from=2015-01-01&to=2022-03-01
+    fn insider_sentiment(
+        &self,
+        symbol: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<InsiderSentimentData, Error> {
+        request(
+            self,
+            "/stock/insider-sentiment",
+            &[
+                ("symbol", symbol.to_string()),
+                ("from", from.to_string()),
+                ("to", to.to_string()),
+            ],
+        )
+    }
+
+    /// /stock/insider-transactions?symbol=TSLA&from=2021-01-01&to=2021-03-31
+    fn insider_transactions(
+        &self,
+        symbol: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<InsiderTransactions, Error> {
+        request(
+            self,
+            "/stock/insider-transactions",
+            &[
+                ("symbol", symbol.to_string()),
+                ("from", from.to_string()),
+                ("to", to.to_string()),
+            ],
+        )
+    }
+
+    /// /calendar/earnings?from=2021-03-01&to=2021-03-09
+    fn earnings_calendar(&self, from: NaiveDate, to: NaiveDate) -> Result<EarningsCalendar, Error> {
+        request(
+            self,
+            "/calendar/earnings",
+            &[("from", from.to_string()), ("to", to.to_string())],
+        )
+    }
+
+    /// /crypto/exchange, just their names
+    fn crypto_exchanges(&self) -> Result<Vec<String>, Error> {
+        request(self, "/crypto/exchange", &[])
+    }
+
+    /// /crypto/symbol?exchange=binance
+    fn crypto_symbols(&self, exchange: &str) -> Result<Vec<CryptoSymbol>, Error> {
+        request(
+            self,
+            "/crypto/symbol",
+            &[("exchange", exchange.to_string())],
+        )
+    }
+
+    /// /crypto/candle?symbol=BINANCE:BTCUSDT&resolution=D&from=1572651390&to=1575243390,
+    /// the same as stock candles
+    fn crypto_candles(
+        &self,
+        symbol: &str,
+        resolution: Resolution,
+        from: i64,
+        to: i64,
+    ) -> Result<Candles, Error> {
+        request(
+            self,
+            "/crypto/candle",
+            &[
+                ("symbol", symbol.to_string()),
+                ("resolution", resolution.to_string()),
+                ("from", from.to_string()),
+                ("to", to.to_string()),
+            ],
+        )
+    }
+
+    /// /forex/exchange, just their names
+    fn forex_exchanges(&self) -> Result<Vec<String>, Error> {
+        request(self, "/forex/exchange", &[])
+    }
+
+    /// /forex/symbol?exchange=oanda
+    fn forex_symbols(&self, exchange: &str) -> Result<Vec<ForexSymbol>, Error> {
+        request(self, "/forex/symbol", &[("exchange", exchange.to_string())])
+    }
+
+    /// /forex/rates?base=USD
+    fn forex_rates(&self, base: &str) -> Result<ForexRates, Error> {
+        request(self, "/forex/rates", &[("base", base.to_string())])
+    }
+
+    /// /stock/peers?symbol=AAPL, which is just a list of symbols
+    fn peers(&self, symbol: &str) -> Result<Vec<String>, Error> {
+        request(self, "/stock/peers", &[("symbol", symbol.to_string())])
+    }
+
+    /// /calendar/ipo?from=2020-04-30&to=2020-06-30
+    fn ipo_calendar(&self, from: NaiveDate, to: NaiveDate) -> Result<IpoCalendar, Error> {
+        request(
+            self,
+            "/calendar/ipo",
+            &[("from", from.to_string()), ("to", to.to_string())],
+        )
+    }
+
+    /// /stock/earnings?symbol=AAPL&limit=4, all the quarters there are without a limit
+    fn eps_surprises(
+        &self,
+        symbol: &str,
+        limit: Option<u32>,
+    ) -> Result<Vec<EarningsSurprise>, Error> {
+        let mut query = vec![("symbol", symbol.to_string())];
+        if let Some(limit) = limit {
+            query.push(("limit", limit.to_string()));
+        }
+        request(self, "/stock/earnings", &query)
+    }
+
+    /// /stock/recommendation?symbol=AAPL
+    fn recommendation_trends(&self, symbol: &str) -> Result<Vec<RecommendationTrend>, Error> {
+        request(
+            self,
+            "/stock/recommendation",
+            &[("symbol", symbol.to_string())],
+        )
+    }
+
+    /// /stock/filings?symbol=AAPL&from=2020-01-01&to=2020-06-11
+    fn sec_filings(
+        &self,
+        symbol: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<SecFiling>, Error> {
+        request(
+            self,
+            "/stock/filings",
+            &[
+                ("symbol", symbol.to_string()),
+                ("from", from.to_string()),
+                ("to", to.to_string()),
+            ],
+        )
+    }
+
+    /// /stock/financials-reported?symbol=AAPL&freq=annual
+    fn financials_reported(
+        &self,
+        symbol: &str,
+        period: Period,
+    ) -> Result<FinancialsReported, Error> {
+        request(
+            self,
+            "/stock/financials-reported",
+            &[("symbol", symbol.to_string()), ("freq", period.to_string())],
+        )
+    }
+
+    /// /stock/metric?symbol=AAPL&metric=all
+    fn basic_financials(&self, symbol: &str) -> Result<BasicFinancials, Error> {
+        request(
+            self,
+            "/stock/metric",
+            &[
+                ("symbol", symbol.to_string()),
+                ("metric", "all".to_string()),
+            ],
+        )
+    }
+}
+
+/// Gets the text for an endpoint and turns it into T
+pub fn request<P, T>(provider: &P, endpoint: &str, query: &[(&str, String)]) -> Result<T, Error>
+where
+    P: MarketDataProvider + ?Sized,
+    T: DeserializeOwned,
+{
+    let text = provider.get_text(endpoint, query)?;
+    serde_json::from_str(&text).with_context(|| {
+        format!(
+            "Couldn't deserialize {endpoint} into {} struct.\nText from provider: '{text}'",
+            std::any::type_name::<T>()
+        )
+    })
+}
+
+/// The real thing: https://finnhub.io
+#[derive(Debug)]
+pub struct FinnhubProvider {
+    // None if no key was found at startup, which turns every request into an error
+    api_key: Option<String>,
+    limiter: RateLimiter,
+    // Where to say that a request is being held up
+    status: Option<SyncSender<Command>>,
+}
+
+impl FinnhubProvider {
+    pub fn new(api_key: Option<String>, limiter: RateLimiter) -> Self {
+        Self {
+            api_key,
+            limiter,
+            status: None,
+        }
+    }
+
+    pub fn with_status(mut self, status: SyncSender<Command>) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    // Doesn't block: a status that doesn't fit in the channel isn't worth waiting for
+    fn report(&self, api_status: ApiStatus) {
+        if let Some(status) = &self.status {
+            let _ = status.try_send(Command::ApiStatus(api_status));
+        }
+    }
+}
+
+impl MarketDataProvider for FinnhubProvider {
+    /// Waits its turn with the rate limiter, then retries 429s and 5xxs with backoff
+    fn get_text(&self, endpoint: &str, query: &[(&str, String)]) -> Result<String, Error> {
+        let Some(api_key) = &self.api_key else {
+            return Err(anyhow::anyhow!(
+                "No Finnhub API key set. Run with --api-key <key>, set FINNHUB_API_KEY, or add api_key to config.json"
+            ));
+        };
+        let url = format!("{FINNHUB_URL}{endpoint}");
+        let mut attempt = 0;
+        loop {
+            let wait = self.limiter.reserve();
+            if !wait.is_zero() {
+                self.report(ApiStatus::Queued { wait });
+                std::thread::sleep(wait);
+            }
+
+            let reply = query
+                .iter()
+                .fold(ureq::get(&url), |request, (key, value)| {
+                    request.query(key, value)
+                })
+                .query("token", api_key)
+                .set("X-Finnhub-Token", api_key)
+                .call();
+            match reply {
+                Err(ureq::Error::Status(401 | 403, _)) => {
+                    return Err(anyhow::anyhow!(format!(
+                        "Finnhub didn't accept the API key for {url}. Check the key and try again"
+                    )))
+                }
+                Err(ureq::Error::Status(status, response))
+                    if (status == 429 || status >= 500) && attempt < MAX_RETRIES =>
+                {
+                    attempt += 1;
+                    let wait = retry_after(response.header("Retry-After"))
+                        .unwrap_or_else(|| backoff(attempt));
+                    self.report(ApiStatus::Retrying {
+                        status,
+                        attempt,
+                        wait,
+                    });
+                    std::thread::sleep(wait);
+                }
+                Err(ureq::Error::Status(429, _)) => {
+                    return Err(anyhow::anyhow!(format!(
+                        "Finnhub is still rate limiting {url} after {MAX_RETRIES} retries. Try again in a minute"
+                    )))
+                }
+                reply => {
+                    return reply
+                        .with_context(|| format!("Couldn't send through url {url}"))?
+                        .into_string()
+                        .with_context(|| "No text for some reason")
+                }
+            }
+        }
+    }
+}
+
+/// Reads replies from JSON files in a directory instead of going over the network,
+/// so the tool runs without an API key. /stock/profile2?symbol=AAPL looks for
+/// stock_profile2_AAPL.json first and then stock_profile2.json.
+#[derive(Debug)]
+pub struct FixtureProvider {
+    dir: PathBuf,
+}
+
+impl FixtureProvider {
+    pub fn new(dir: impl AsRef<Path>) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+
+    fn file_names(endpoint: &str, query: &[(&str, String)]) -> Vec<String> {
+        let name = endpoint.trim_matches('/').replace('/', "_");
+        let mut file_names = Vec::new();
+        if let Some((_, value)) = query.first() {
+            let value = value.replace(|c: char| !c.is_alphanumeric(), "_");
+            file_names.push(format!("{name}_{value}.json"));
+        }
+        file_names.push(format!("{name}.json"));
+        file_names
+    }
+}
+
+impl MarketDataProvider for FixtureProvider {
+    fn get_text(&self, endpoint: &str, query: &[(&str, String)]) -> Result<String, Error> {
+        let file_names = Self::file_names(endpoint, query);
+        file_names
+            .iter()
+            .map(|file_name| self.dir.join(file_name))
+            .find(|path| path.exists())
+            .ok_or_else(|| {
+                anyhow::anyhow!(format!(
+                    "No fixture for {endpoint} in {}, looked for {}",
+                    self.dir.display(),
+                    file_names.join(" and ")
+                ))
+            })
+            .and_then(|path| {
+                std::fs::read_to_string(&path)
+                    .with_context(|| format!("Couldn't read fixture {}", path.display()))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixtures() -> FixtureProvider {
+        FixtureProvider::new(Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures"))
+    }
+
+    #[test]
+    fn file_names_try_the_first_query_value_first() {
+        assert_eq!(
+            FixtureProvider::file_names("/stock/profile2", &[("symbol", "BRK.A".to_string())]),
+            ["stock_profile2_BRK_A.json", "stock_profile2.json"]
+        );
+        assert_eq!(FixtureProvider::file_names("/news", &[]), ["news.json"]);
+    }
+
+    #[test]
+    fn profile_falls_back_to_the_endpoint_fixture() {
+        // There's no stock_profile2_TSLA.json
+        let profile = fixtures().company_profile("TSLA").unwrap();
+        assert!(profile.to_string().contains("Apple Inc"));
+    }
+
+    #[test]
+    fn quote_reads_the_fixture() {
+        let quote = fixtures().quote("AAPL").unwrap();
+        assert_eq!(quote.c, 261.74);
+        assert_eq!(quote.pc, 259.45);
+    }
+
+    #[test]
+    fn candles_read_the_fixture() {
+        let candles = fixtures().candles("AAPL", Resolution::Day, 0, 1).unwrap();
+        assert_eq!(candles.s, "ok");
+        assert_eq!(candles.c.len(), 120);
+        assert_eq!(candles.t.len(), candles.c.len());
+    }
+
+    #[test]
+    fn news_reads_the_fixtures() {
+        assert_eq!(fixtures().market_news("general", 0).unwrap().len(), 3);
+        assert_eq!(fixtures().market_news("crypto", 0).unwrap().len(), 4);
+        let day = NaiveDate::from_ymd(2023, 1, 1);
+        assert_eq!(fixtures().company_news("AAPL", day, day).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn missing_fixture_says_where_it_looked() {
+        let error = fixtures()
+            .get_text("/stock/nothing", &[("symbol", "AAPL".to_string())])
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("stock_nothing_AAPL.json and stock_nothing.json"));
+    }
+}
@@ -0,0 +1,327 @@
+//! Analyst recommendation trends for the Results window: how many analysts said strong buy
+//! down to strong sell each month as stacked bars, with a consensus score for each month.
+
+use tui::{
+    backend::Backend,
+    buffer::Buffer,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table, Widget},
+    Frame,
+};
+
+use crate::api::RecommendationTrend;
+
+/// Bottom to top in each bar, with the score each one counts for
+const RATINGS: [(&str, Color, f64); 5] = [
+    ("Strong sell", Color::Red, 5.0),
+    ("Sell", Color::LightRed, 4.0),
+    ("Hold", Color::Yellow, 3.0),
+    ("Buy", Color::LightGreen, 2.0),
+    ("Strong buy", Color::Green, 1.0),
+];
+
+/// How many analysts gave each rating, in the same order as RATINGS
+fn counts(trend: &RecommendationTrend) -> [u32; 5] {
+    [
+        trend.strong_sell,
+        trend.sell,
+        trend.hold,
+        trend.buy,
+        trend.strong_buy,
+    ]
+}
+
+/// Average rating from 1 for everyone saying strong buy to 5 for everyone saying strong sell
+pub fn consensus(trend: &RecommendationTrend) -> Option<f64> {
+    let counts = counts(trend);
+    let total = counts.iter().sum::<u32>();
+    if total == 0 {
+        return None;
+    }
+    let weighted = counts
+        .iter()
+        .zip(RATINGS)
+        .map(|(count, (_, _, score))| *count as f64 * score)
+        .sum::<f64>();
+    Some(weighted / total as f64)
+}
+
+/// Kept in State so it can be redrawn at whatever size the terminal currently is
+#[derive(Debug, Clone)]
+pub struct RecommendationChart {
+    pub symbol: String,
+    // Oldest month first
+    pub periods: Vec<RecommendationTrend>,
+}
+
+/// One column of stacked blocks per period, as many periods as fit with the newest kept
+struct StackedBars<'a> {
+    periods: &'a [RecommendationTrend],
+}
+
+impl Widget for StackedBars<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        // A bar is 7 wide with a space after it, and the bottom line is for the month
+        let bar_width = 7;
+        let fits = (area.width / (bar_width + 1)) as usize;
+        let periods = &self.periods[self.periods.len().saturating_sub(fits)..];
+        let height = area.height.saturating_sub(1);
+        let most = periods
+            .iter()
+            .map(|trend| counts(trend).iter().sum::<u32>())
+            .max()
+            .unwrap_or(0)
+            .max(1);
+
+        for (index, trend) in periods.iter().enumerate() {
+            let x = area.x + index as u16 * (bar_width + 1);
+            // Rounding each part on its own would make bars with the same total differ in height
+            let mut stacked = 0;
+            let mut drawn = 0;
+            for (count, (_, colour, _)) in counts(trend).iter().zip(RATINGS) {
+                stacked += count;
+                let top = (stacked as f64 / most as f64 * height as f64).round() as u16;
+                for row in drawn..top {
+                    let y = area.y + height - 1 - row;
+                    for dx in 0..bar_width {
+                        buf.get_mut(x + dx, y)
+                            .set_symbol("█")
+                            .set_style(Style::default().fg(colour));
+                    }
+                }
+                // The count goes in the middle of its part of the bar if there's room
+                if top > drawn && *count > 0 {
+                    let y = area.y + height - 1 - (drawn + top - 1) / 2;
+                    let text = count.to_string();
+                    buf.set_string(
+                        x + (bar_width.saturating_sub(text.len() as u16)) / 2,
+                        y,
+                        text,
+                        Style::default().fg(Color::Black).bg(colour),
+                    );
+                }
+                drawn = top;
+            }
+            // 2020-03-01 becomes 2020-03
+            let month = trend.period.get(..7).unwrap_or(&trend.period);
+            buf.set_string(x, area.y + height, month, Style::default().fg(Color::Gray));
+        }
+    }
+}
+
+impl RecommendationChart {
+    pub fn new(symbol: String, mut periods: Vec<RecommendationTrend>) -> Self {
+        // The periods are YYYY-MM-DD so they sort as text
+        periods.sort_by(|a, b| a.period.cmp(&b.period));
+        Self { symbol, periods }
+    }
+
+    /// The latest consensus and how much it moved since the month before
+    pub fn summary(&self) -> String {
+        let mut scored = self
+            .periods
+            .iter()
+            .rev()
+            .filter_map(|trend| Some((trend, consensus(trend)?)));
+        match (scored.next(), scored.next()) {
+            (Some((_, latest)), Some((previous, earlier))) => format!(
+                "consensus {latest:.2}, {} from {}",
+                Self::movement(latest - earlier),
+                previous.period.get(..7).unwrap_or(&previous.period)
+            ),
+            (Some((_, latest)), None) => format!("consensus {latest:.2}"),
+            _ => "no ratings".to_string(),
+        }
+    }
+
+    /// Falling is more bullish because 1 is strong buy
+    fn movement(change: f64) -> String {
+        if change.abs() < 0.005 {
+            "unchanged".to_string()
+        } else if change < 0.0 {
+            format!("{change:+.2} more bullish")
+        } else {
+            format!("{change:+.2} more bearish")
+        }
+    }
+
+    pub fn render<B: Backend>(&self, f: &mut Frame<B>, area: Rect, block: Block) {
+        let inner = block.inner(area);
+        f.render_widget(block, area);
+        let chart_and_table = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Percentage(55), Constraint::Percentage(45)].as_ref())
+            .split(inner);
+
+        let legend = RATINGS
+            .iter()
+            .rev()
+            .flat_map(|(name, colour, _)| {
+                [
+                    Span::styled("█ ", Style::default().fg(*colour)),
+                    Span::raw(format!("{name}  ")),
+                ]
+            })
+            .collect::<Vec<_>>();
+        let chart_block = Block::default()
+            .title("Analysts per month")
+            .borders(Borders::ALL);
+        let chart_area = chart_block.inner(chart_and_table[0]);
+        f.render_widget(chart_block, chart_and_table[0]);
+        let legend_and_bars = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(0)].as_ref())
+            .split(chart_area);
+        f.render_widget(Paragraph::new(Spans::from(legend)), legend_and_bars[0]);
+        f.render_widget(
+            StackedBars {
+                periods: &self.periods,
+            },
+            legend_and_bars[1],
+        );
+
+        // Newest first, each with how its consensus moved from the month before
+        let rows = self.periods.iter().enumerate().rev().map(|(index, trend)| {
+            let score = consensus(trend);
+            let previous = index
+                .checked_sub(1)
+                .and_then(|index| consensus(&self.periods[index]));
+            let change = match (score, previous) {
+                (Some(score), Some(previous)) => {
+                    let change = score - previous;
+                    let colour = if change.abs() < 0.005 {
+                        Color::Gray
+                    } else if change < 0.0 {
+                        Color::Green
+                    } else {
+                        Color::Red
+                    };
+                    Cell::from(Self::movement(change)).style(Style::default().fg(colour))
+                }
+                _ => Cell::from("-"),
+            };
+            let mut cells = vec![Cell::from(trend.period.clone())];
+            cells.extend(
+                counts(trend)
+                    .iter()
+                    .rev()
+                    .map(|count| Cell::from(count.to_string())),
+            );
+            cells.push(Cell::from(
+                score.map_or("-".to_string(), |score| format!("{score:.2}")),
+            ));
+            cells.push(change);
+            Row::new(cells)
+        });
+        let table = Table::new(rows)
+            .header(
+                Row::new(vec![
+                    "Period",
+                    "Strong buy",
+                    "Buy",
+                    "Hold",
+                    "Sell",
+                    "Strong sell",
+                    "Score",
+                    "Change",
+                ])
+                .style(Style::default().add_modifier(Modifier::BOLD)),
+            )
+            .block(Block::default().borders(Borders::ALL))
+            .style(Style::default().fg(Color::White).bg(Color::Black))
+            .widths(&[
+                Constraint::Length(10),
+                Constraint::Length(10),
+                Constraint::Length(5),
+                Constraint::Length(5),
+                Constraint::Length(5),
+                Constraint::Length(11),
+                Constraint::Length(6),
+                Constraint::Min(18),
+            ])
+            .column_spacing(1);
+        f.render_widget(table, chart_and_table[1]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trend(
+        period: &str,
+        [strong_buy, buy, hold, sell, strong_sell]: [u32; 5],
+    ) -> RecommendationTrend {
+        RecommendationTrend {
+            symbol: "AAPL".to_string(),
+            period: period.to_string(),
+            strong_buy,
+            buy,
+            hold,
+            sell,
+            strong_sell,
+        }
+    }
+
+    #[test]
+    fn consensus_runs_from_strong_buy_to_strong_sell() {
+        assert_eq!(consensus(&trend("2020-01-01", [4, 0, 0, 0, 0])), Some(1.0));
+        assert_eq!(consensus(&trend("2020-01-01", [0, 0, 0, 0, 3])), Some(5.0));
+        // (2 * 1 + 1 * 2 + 1 * 3) / 4
+        assert_eq!(consensus(&trend("2020-01-01", [2, 1, 1, 0, 0])), Some(1.75));
+        assert_eq!(consensus(&trend("2020-01-01", [0; 5])), None);
+    }
+
+    #[test]
+    fn summary_compares_with_the_month_before() {
+        // Out of order on purpose, new sorts them by period
+        let chart = RecommendationChart::new(
+            "AAPL".to_string(),
+            vec![
+                trend("2020-03-01", [2, 2, 0, 0, 0]),
+                trend("2020-02-01", [0, 4, 0, 0, 0]),
+            ],
+        );
+        assert_eq!(chart.periods[0].period, "2020-02-01");
+        assert_eq!(
+            chart.summary(),
+            "consensus 1.50, -0.50 more bullish from 2020-02"
+        );
+
+        let chart = RecommendationChart::new(
+            "AAPL".to_string(),
+            vec![
+                trend("2020-02-01", [0, 4, 0, 0, 0]),
+                trend("2020-03-01", [0, 0, 4, 0, 0]),
+            ],
+        );
+        assert_eq!(
+            chart.summary(),
+            "consensus 3.00, +1.00 more bearish from 2020-02"
+        );
+    }
+
+    #[test]
+    fn summary_skips_months_without_ratings() {
+        let chart = RecommendationChart::new(
+            "AAPL".to_string(),
+            vec![
+                trend("2020-01-01", [0, 4, 0, 0, 0]),
+                trend("2020-02-01", [0; 5]),
+                trend("2020-03-01", [0, 4, 0, 0, 0]),
+            ],
+        );
+        assert_eq!(chart.summary(), "consensus 2.00, unchanged from 2020-01");
+        let chart = RecommendationChart::new(
+            "AAPL".to_string(),
+            vec![trend("2020-01-01", [1, 0, 0, 0, 0])],
+        );
+        assert_eq!(chart.summary(), "consensus 1.00");
+        assert_eq!(
+            RecommendationChart::new("AAPL".to_string(), Vec::new()).summary(),
+            "no ratings"
+        );
+    }
+}
@@ -0,0 +1,466 @@
+//! Financials as reported for the Results window: the balance sheet, cash flow and
+//! income statement line items of a 10-K or 10-Q, optionally next to another filing.
+
+use anyhow::{Context, Error};
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Cell, Paragraph, Row, Table, TableState},
+    Frame,
+};
+
+use crate::{
+    api::{ReportedFiling, Statement},
+    financials::Period,
+};
+
+/// A year and, for 10-Qs, a quarter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FilingPeriod {
+    pub year: i32,
+    pub quarter: Option<u32>,
+}
+
+impl FilingPeriod {
+    fn matches(&self, filing: &ReportedFiling) -> bool {
+        filing.year == self.year && filing.quarter == self.quarter.unwrap_or(0)
+    }
+}
+
+impl std::fmt::Display for FilingPeriod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.quarter {
+            Some(quarter) => write!(f, "{} Q{quarter}", self.year),
+            None => write!(f, "FY{}", self.year),
+        }
+    }
+}
+
+/// Typed into the search box as SYMBOL [YEAR [QUARTER]] [vs YEAR [QUARTER]],
+/// e.g. "AAPL 2021 vs 2019" or "AAPL 2022 Q2 vs 2021 Q2"
+#[derive(Debug, Clone)]
+pub struct ReportedRequest {
+    pub symbol: String,
+    // Quarterly as soon as either side names a quarter
+    pub period: Period,
+    // The newest filing when None
+    pub filing: Option<FilingPeriod>,
+    pub compare: Option<FilingPeriod>,
+}
+
+impl ReportedRequest {
+    pub fn parse(input: &str, current: Option<&str>) -> Result<Self, Error> {
+        let mut words = input.split_whitespace().collect::<Vec<_>>();
+        let is_symbol =
+            |word: &str| word.parse::<i32>().is_err() && !word.eq_ignore_ascii_case("vs");
+        let symbol = match (words.first(), current) {
+            (Some(word), _) if is_symbol(word) => words.remove(0).to_uppercase(),
+            (_, Some(current)) => current.to_string(),
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "Type a symbol first, e.g. AAPL 2021 vs 2019 or AAPL 2022 Q2"
+                ))
+            }
+        };
+        let (filing, compare) = match words
+            .iter()
+            .position(|word| word.eq_ignore_ascii_case("vs"))
+        {
+            Some(index) => (
+                Self::filing_period(&words[..index])?,
+                Self::filing_period(&words[index + 1..])?,
+            ),
+            None => (Self::filing_period(&words)?, None),
+        };
+        if compare.is_some() && filing.is_none() {
+            return Err(anyhow::anyhow!(
+                "Say which filing to compare with, e.g. AAPL 2021 vs 2019"
+            ));
+        }
+        if let (Some(filing), Some(compare)) = (filing, compare) {
+            if filing.quarter.is_some() != compare.quarter.is_some() {
+                return Err(anyhow::anyhow!(format!(
+                    "Can't compare {filing} with {compare}, give both a quarter or neither"
+                )));
+            }
+        }
+        let period = if filing.is_some_and(|filing| filing.quarter.is_some()) {
+            Period::Quarterly
+        } else {
+            Period::Annual
+        };
+        Ok(Self {
+            symbol,
+            period,
+            filing,
+            compare,
+        })
+    }
+
+    /// YEAR [QUARTER], with the quarter as 2 or Q2
+    fn filing_period(words: &[&str]) -> Result<Option<FilingPeriod>, Error> {
+        let year = match words.first() {
+            Some(year) => year
+                .parse()
+                .with_context(|| format!("Couldn't read {year} as a year"))?,
+            None => return Ok(None),
+        };
+        let quarter = match words.get(1) {
+            Some(quarter) => {
+                let number = quarter.trim_start_matches(['Q', 'q']);
+                match number.parse() {
+                    Ok(quarter @ 1..=4) => Some(quarter),
+                    _ => {
+                        return Err(anyhow::anyhow!(format!(
+                            "Couldn't read {quarter} as a quarter, use 1 to 4 or Q1 to Q4"
+                        )))
+                    }
+                }
+            }
+            None => None,
+        };
+        if words.len() > 2 {
+            return Err(anyhow::anyhow!(format!(
+                "Didn't expect {}, use YEAR [QUARTER] [vs YEAR [QUARTER]]",
+                words[2..].join(" ")
+            )));
+        }
+        Ok(Some(FilingPeriod { year, quarter }))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatementKind {
+    BalanceSheet,
+    CashFlow,
+    Income,
+}
+
+impl StatementKind {
+    pub fn next(self) -> Self {
+        match self {
+            StatementKind::BalanceSheet => StatementKind::CashFlow,
+            StatementKind::CashFlow => StatementKind::Income,
+            StatementKind::Income => StatementKind::BalanceSheet,
+        }
+    }
+
+    fn of(self, filing: &ReportedFiling) -> &Statement {
+        match self {
+            StatementKind::BalanceSheet => &filing.report.bs,
+            StatementKind::CashFlow => &filing.report.cf,
+            StatementKind::Income => &filing.report.ic,
+        }
+    }
+}
+
+impl std::fmt::Display for StatementKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let output = match self {
+            StatementKind::BalanceSheet => "balance sheet",
+            StatementKind::CashFlow => "cash flow",
+            StatementKind::Income => "income statement",
+        };
+        write!(f, "{}", output)
+    }
+}
+
+/// One row of the table: the line item with its value in the shown and compared filings
+struct LineRow<'a> {
+    label: &'a str,
+    value: Option<f64>,
+    compare: Option<f64>,
+}
+
+/// Kept in State so the filing, statement and selection survive redraws
+#[derive(Debug, Clone)]
+pub struct ReportedView {
+    pub symbol: String,
+    pub period: Period,
+    // Newest first
+    pub filings: Vec<ReportedFiling>,
+    // Indexes into filings
+    pub shown: usize,
+    pub compare: Option<usize>,
+    pub statement: StatementKind,
+    pub selected: usize,
+}
+
+/// 338,516,000,000 for amounts, 2.97 for per share numbers
+fn amount(value: f64) -> String {
+    if value.fract() != 0.0 && value.abs() < 1000.0 {
+        return format!("{value:.2}");
+    }
+    let digits = (value.round() as i64).unsigned_abs().to_string();
+    let mut output = String::new();
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index).is_multiple_of(3) {
+            output.push(',');
+        }
+        output.push(digit);
+    }
+    if value < 0.0 {
+        output.insert(0, '-');
+    }
+    output
+}
+
+impl ReportedView {
+    pub fn new(request: ReportedRequest, mut filings: Vec<ReportedFiling>) -> Result<Self, Error> {
+        filings.sort_by_key(|filing| std::cmp::Reverse((filing.year, filing.quarter)));
+        let find = |period: FilingPeriod| {
+            filings
+                .iter()
+                .position(|filing| period.matches(filing))
+                .ok_or_else(|| {
+                    let available = filings
+                        .iter()
+                        .map(Self::filing_label)
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    anyhow::anyhow!(format!(
+                        "No {period} filing for {}, there's {available}",
+                        request.symbol
+                    ))
+                })
+        };
+        let shown = match request.filing {
+            Some(period) => find(period)?,
+            None => 0,
+        };
+        let compare = request.compare.map(find).transpose()?;
+        Ok(Self {
+            symbol: request.symbol,
+            period: request.period,
+            filings,
+            shown,
+            compare,
+            statement: StatementKind::BalanceSheet,
+            selected: 0,
+        })
+    }
+
+    /// FY2021 10-K, 2022 Q2 10-Q
+    pub fn filing_label(filing: &ReportedFiling) -> String {
+        let period = FilingPeriod {
+            year: filing.year,
+            quarter: (filing.quarter != 0).then_some(filing.quarter),
+        };
+        format!("{period} {}", filing.form)
+    }
+
+    pub fn shown_label(&self) -> String {
+        let shown = Self::filing_label(&self.filings[self.shown]);
+        match self.compare {
+            Some(compare) => format!("{shown} vs {}", Self::filing_label(&self.filings[compare])),
+            None => shown,
+        }
+    }
+
+    /// s cycles the statements, n and o show a newer or older filing, and v compares
+    /// with the filing being shown (again to stop comparing)
+    pub fn key(&mut self, c: char) {
+        match c {
+            's' => {
+                self.statement = self.statement.next();
+                self.selected = 0;
+            }
+            'n' => self.shown = self.shown.saturating_sub(1),
+            'o' if self.shown + 1 < self.filings.len() => self.shown += 1,
+            'v' => {
+                self.compare = match self.compare {
+                    Some(_) => None,
+                    None => Some(self.shown),
+                }
+            }
+            _ => {}
+        }
+        self.selected = self.selected.min(self.line_rows().len().saturating_sub(1));
+    }
+
+    pub fn up(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn down(&mut self) {
+        if self.selected + 1 < self.line_rows().len() {
+            self.selected += 1;
+        }
+    }
+
+    /// The shown filing's line items in its own order, then any only the compared one has
+    fn line_rows(&self) -> Vec<LineRow<'_>> {
+        let shown = self.statement.of(&self.filings[self.shown]);
+        let compare = self
+            .compare
+            .map(|compare| self.statement.of(&self.filings[compare]));
+        let mut rows = shown
+            .0
+            .iter()
+            .map(|item| LineRow {
+                label: &item.label,
+                value: item.value,
+                compare: compare.and_then(|compare| compare.value(&item.concept)),
+            })
+            .collect::<Vec<_>>();
+        if let Some(compare) = compare {
+            rows.extend(
+                compare
+                    .0
+                    .iter()
+                    .filter(|item| shown.get(&item.concept).is_none())
+                    .map(|item| LineRow {
+                        label: &item.label,
+                        value: None,
+                        compare: item.value,
+                    }),
+            );
+        }
+        rows
+    }
+
+    pub fn render<B: Backend>(&self, f: &mut Frame<B>, area: Rect, block: Block) {
+        let inner = block.inner(area);
+        f.render_widget(block, area);
+        let info_and_table = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(2), Constraint::Min(0)].as_ref())
+            .split(inner);
+
+        // Finnhub's dates come with a time of 00:00:00 on the end
+        let day = |date: &str| date.get(..10).unwrap_or(date).to_string();
+        let describe = |index: usize| {
+            let filing = &self.filings[index];
+            format!(
+                "{}: {} to {}, filed {}",
+                Self::filing_label(filing),
+                day(&filing.start_date),
+                day(&filing.end_date),
+                day(&filing.filed_date)
+            )
+        };
+        let mut info = describe(self.shown);
+        if let Some(compare) = self.compare {
+            info.push('\n');
+            info.push_str(&describe(compare));
+        }
+        f.render_widget(
+            Paragraph::new(info).style(Style::default().fg(Color::Gray).bg(Color::Black)),
+            info_and_table[0],
+        );
+
+        let line_rows = self.line_rows();
+        let text = |value: Option<f64>| value.map_or("-".to_string(), amount);
+        let rows = line_rows.iter().map(|row| {
+            let mut cells = vec![Cell::from(row.label), Cell::from(text(row.value))];
+            if self.compare.is_some() {
+                cells.push(Cell::from(text(row.compare)));
+                if let (Some(value), Some(compare)) = (row.value, row.compare) {
+                    let change = value - compare;
+                    let colour = Style::default().fg(if change >= 0.0 {
+                        Color::Green
+                    } else {
+                        Color::Red
+                    });
+                    let percent = if compare != 0.0 {
+                        format!("{:+.1}%", change / compare.abs() * 100.0)
+                    } else {
+                        "-".to_string()
+                    };
+                    let sign = if change > 0.0 { "+" } else { "" };
+                    cells.push(Cell::from(format!("{sign}{}", amount(change))).style(colour));
+                    cells.push(Cell::from(percent).style(colour));
+                }
+            }
+            Row::new(cells)
+        });
+
+        let (header, widths) = match self.compare {
+            Some(compare) => (
+                vec![
+                    "Line item".to_string(),
+                    Self::filing_label(&self.filings[self.shown]),
+                    Self::filing_label(&self.filings[compare]),
+                    "Change".to_string(),
+                    "Change %".to_string(),
+                ],
+                vec![
+                    Constraint::Percentage(34),
+                    Constraint::Percentage(18),
+                    Constraint::Percentage(18),
+                    Constraint::Percentage(18),
+                    Constraint::Percentage(10),
+                ],
+            ),
+            None => (
+                vec![
+                    "Line item".to_string(),
+                    Self::filing_label(&self.filings[self.shown]),
+                ],
+                vec![Constraint::Percentage(60), Constraint::Percentage(38)],
+            ),
+        };
+        let table = Table::new(rows)
+            .header(Row::new(header).style(Style::default().add_modifier(Modifier::BOLD)))
+            .style(Style::default().fg(Color::White).bg(Color::Black))
+            .widths(&widths)
+            .column_spacing(1)
+            .highlight_style(Style::default().bg(Color::Gray))
+            .highlight_symbol(">>");
+        let mut table_state = TableState::default();
+        if !line_rows.is_empty() {
+            table_state.select(Some(self.selected));
+        }
+        f.render_stateful_widget(table, info_and_table[1], &mut table_state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_reads_a_comparison() {
+        let request = ReportedRequest::parse("aapl 2022 Q2 vs 2021 2", None).unwrap();
+        assert_eq!(request.symbol, "AAPL");
+        assert_eq!(request.period, Period::Quarterly);
+        assert_eq!(
+            request.filing,
+            Some(FilingPeriod {
+                year: 2022,
+                quarter: Some(2)
+            })
+        );
+        assert_eq!(
+            request.compare,
+            Some(FilingPeriod {
+                year: 2021,
+                quarter: Some(2)
+            })
+        );
+    }
+
+    #[test]
+    fn parse_defaults_to_the_newest_annual_filing() {
+        let request = ReportedRequest::parse("", Some("MSFT")).unwrap();
+        assert_eq!(request.symbol, "MSFT");
+        assert_eq!(request.period, Period::Annual);
+        assert_eq!(request.filing, None);
+        assert_eq!(request.compare, None);
+
+        let request = ReportedRequest::parse("2021 vs 2019", Some("MSFT")).unwrap();
+        assert_eq!(request.symbol, "MSFT");
+        assert_eq!(request.filing.map(|filing| filing.year), Some(2021));
+        assert_eq!(request.compare.map(|filing| filing.year), Some(2019));
+    }
+
+    #[test]
+    fn parse_rejects_bad_input() {
+        assert!(ReportedRequest::parse("", None).is_err());
+        assert!(ReportedRequest::parse("AAPL vs 2019", None).is_err());
+        assert!(ReportedRequest::parse("AAPL 2022 Q2 vs 2021", None).is_err());
+        assert!(ReportedRequest::parse("AAPL 2022 Q5", None).is_err());
+        assert!(ReportedRequest::parse("AAPL 2022 Q2 extra", None).is_err());
+    }
+}
@@ -0,0 +1,252 @@
+//! A list of symbols that stays on screen next to the Results window, saved between runs.
+
+use std::{collections::HashMap, path::PathBuf};
+
+use anyhow::{Context, Error};
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Cell, Row, Table, TableState},
+    Frame,
+};
+
+use crate::{api::Quote, config::data_dir};
+
+/// How often the watchlist's quotes are refreshed
+pub const WATCHLIST_REFRESH_SECS: u64 = 30;
+
+#[derive(Debug, Default)]
+pub struct Watchlist {
+    pub symbols: Vec<String>,
+    // Latest quote for each symbol, or why there isn't one
+    pub quotes: HashMap<String, Result<Quote, String>>,
+    pub selected: usize,
+    // None when there's nowhere to save it, in which case it only lasts until the tool closes
+    path: Option<PathBuf>,
+}
+
+impl Watchlist {
+    /// Reads watchlist.json from the data directory, which is fine to be missing
+    pub fn load() -> Result<Self, Error> {
+        let path = data_dir().map(|dir| dir.join("watchlist.json"));
+        let symbols = match &path {
+            Some(path) if path.exists() => {
+                let text = std::fs::read_to_string(path)
+                    .with_context(|| format!("Couldn't read watchlist {}", path.display()))?;
+                serde_json::from_str(&text)
+                    .with_context(|| format!("Couldn't understand watchlist {}", path.display()))?
+            }
+            _ => Vec::new(),
+        };
+        Ok(Self {
+            symbols,
+            path,
+            ..Default::default()
+        })
+    }
+
+    fn save(&self) -> Result<(), Error> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("Couldn't make directory {}", dir.display()))?;
+        }
+        let json = serde_json::to_string_pretty(&self.symbols)?;
+        std::fs::write(path, json)
+            .with_context(|| format!("Couldn't save watchlist to {}", path.display()))
+    }
+
+    pub fn add(&mut self, symbol: &str) -> Result<(), Error> {
+        let symbol = symbol.trim().to_uppercase();
+        if symbol.is_empty() {
+            return Err(anyhow::anyhow!(
+                "Type a symbol into the search box to add it to the watchlist"
+            ));
+        }
+        if self.symbols.contains(&symbol) {
+            return Err(anyhow::anyhow!(format!(
+                "{symbol} is already on the watchlist"
+            )));
+        }
+        self.symbols.push(symbol);
+        self.selected = self.symbols.len() - 1;
+        self.save()
+    }
+
+    pub fn remove_selected(&mut self) -> Result<(), Error> {
+        if self.selected >= self.symbols.len() {
+            return Ok(());
+        }
+        let symbol = self.symbols.remove(self.selected);
+        self.quotes.remove(&symbol);
+        self.selected = self.selected.min(self.symbols.len().saturating_sub(1));
+        self.save()
+    }
+
+    pub fn up(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn down(&mut self) {
+        if self.selected + 1 < self.symbols.len() {
+            self.selected += 1;
+        }
+    }
+
+    /// Moves the selected symbol one place up the list
+    pub fn move_up(&mut self) -> Result<(), Error> {
+        if self.selected == 0 || self.selected >= self.symbols.len() {
+            return Ok(());
+        }
+        self.symbols.swap(self.selected, self.selected - 1);
+        self.selected -= 1;
+        self.save()
+    }
+
+    /// Moves the selected symbol one place down the list
+    pub fn move_down(&mut self) -> Result<(), Error> {
+        if self.selected + 1 >= self.symbols.len() {
+            return Ok(());
+        }
+        self.symbols.swap(self.selected, self.selected + 1);
+        self.selected += 1;
+        self.save()
+    }
+
+    pub fn selected_symbol(&self) -> Option<&String> {
+        self.symbols.get(self.selected)
+    }
+
+    pub fn update_quotes(&mut self, quotes: Vec<(String, Result<Quote, String>)>) {
+        self.quotes.extend(quotes);
+    }
+
+    pub fn render<B: Backend>(&self, f: &mut Frame<B>, area: Rect, block: Block) {
+        let rows = self
+            .symbols
+            .iter()
+            .map(|symbol| match self.quotes.get(symbol) {
+                Some(Ok(quote)) => {
+                    let colour = if quote.change() >= 0.0 {
+                        Color::Green
+                    } else {
+                        Color::Red
+                    };
+                    Row::new(vec![
+                        Cell::from(symbol.clone()),
+                        Cell::from(format!("{:.2}", quote.c)),
+                        Cell::from(format!("{:+.2}%", quote.percent_change()))
+                            .style(Style::default().fg(colour)),
+                        Cell::from(format!("{:.2}-{:.2}", quote.l, quote.h)),
+                    ])
+                }
+                Some(Err(_)) => Row::new(vec![symbol.clone(), "n/a".into()]),
+                None => Row::new(vec![symbol.clone(), "...".into()]),
+            });
+
+        let table = Table::new(rows)
+            .header(
+                Row::new(vec!["Symbol", "Last", "Chg %", "Low-high"])
+                    .style(Style::default().add_modifier(Modifier::BOLD)),
+            )
+            .block(block)
+            .style(Style::default().fg(Color::White).bg(Color::Black))
+            .widths(&[
+                Constraint::Length(7),
+                Constraint::Length(9),
+                Constraint::Length(8),
+                Constraint::Min(13),
+            ])
+            .column_spacing(1)
+            .highlight_style(Style::default().bg(Color::Gray))
+            .highlight_symbol(">>");
+
+        let mut table_state = TableState::default();
+        if !self.symbols.is_empty() {
+            table_state.select(Some(self.selected));
+        }
+        f.render_stateful_widget(table, area, &mut table_state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn watchlist(symbols: &[&str]) -> Watchlist {
+        let mut watchlist = Watchlist::default();
+        for symbol in symbols {
+            watchlist.add(symbol).unwrap();
+        }
+        watchlist
+    }
+
+    #[test]
+    fn add_selects_the_new_symbol() {
+        let mut watchlist = watchlist(&["aapl", " msft "]);
+        assert_eq!(watchlist.symbols, ["AAPL", "MSFT"]);
+        assert_eq!(
+            watchlist.selected_symbol().map(String::as_str),
+            Some("MSFT")
+        );
+        assert!(watchlist.add("AAPL").is_err());
+        assert!(watchlist.add("  ").is_err());
+        assert_eq!(watchlist.symbols.len(), 2);
+    }
+
+    #[test]
+    fn remove_keeps_the_selection_in_the_list() {
+        let mut watchlist = watchlist(&["AAPL", "MSFT", "IBM"]);
+        watchlist.remove_selected().unwrap();
+        assert_eq!(watchlist.symbols, ["AAPL", "MSFT"]);
+        assert_eq!(watchlist.selected, 1);
+        watchlist.remove_selected().unwrap();
+        watchlist.remove_selected().unwrap();
+        assert!(watchlist.symbols.is_empty());
+        assert_eq!(watchlist.selected, 0);
+        assert!(watchlist.remove_selected().is_ok());
+    }
+
+    #[test]
+    fn moving_stops_at_the_ends() {
+        let mut watchlist = watchlist(&["AAPL", "MSFT", "IBM"]);
+        watchlist.move_down().unwrap();
+        assert_eq!(watchlist.symbols, ["AAPL", "MSFT", "IBM"]);
+        watchlist.move_up().unwrap();
+        watchlist.move_up().unwrap();
+        watchlist.move_up().unwrap();
+        assert_eq!(watchlist.symbols, ["IBM", "AAPL", "MSFT"]);
+        assert_eq!(watchlist.selected, 0);
+        watchlist.down();
+        watchlist.down();
+        watchlist.down();
+        assert_eq!(watchlist.selected, 2);
+        watchlist.up();
+        assert_eq!(
+            watchlist.selected_symbol().map(String::as_str),
+            Some("AAPL")
+        );
+    }
+
+    #[test]
+    fn changes_are_saved() {
+        let path = std::env::temp_dir().join(format!(
+            "finance-tool-watchlist-test-{}.json",
+            std::process::id()
+        ));
+        let mut watchlist = Watchlist {
+            path: Some(path.clone()),
+            ..Default::default()
+        };
+        watchlist.add("AAPL").unwrap();
+        watchlist.add("MSFT").unwrap();
+        watchlist.move_up().unwrap();
+        let saved: Vec<String> =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(saved, ["MSFT", "AAPL"]);
+    }
+}